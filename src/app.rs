@@ -13,16 +13,22 @@ use iced::{
     },
 };
 
+mod compressed_format;
+mod gx_format;
 mod image;
 mod image_format;
 mod message;
 mod pixel_format;
+mod swizzle;
 
 use crate::SPACING;
-use image::Image;
-use image_format::{ImageFormat, PaletteInfo, TileInfo};
+use compressed_format::CompressedFormatState;
+use gx_format::GxFormatState;
+use image::{Image, palette};
+use image_format::{BitOrder, Bpp, ImageFormat, PaletteFile, PaletteInfo, TileInfo};
 use message::{Message, SaveFormat, TextInput};
 use pixel_format::PixelFormatState;
+use swizzle::SwizzleState;
 
 #[derive(Debug)]
 pub struct App {
@@ -33,8 +39,12 @@ pub struct App {
     pixel_format: PixelFormatState,
     ignore_alpha: bool,
     image_format: ImageFormat,
+    compressed_format: CompressedFormatState,
+    gx_format: GxFormatState,
     palette: PaletteInfo,
     tile: TileInfo,
+    swizzle: SwizzleState,
+    bit_order: BitOrder,
     image: Option<Handle>,
     error: Option<String>,
     filter_method: FilterMethod,
@@ -50,8 +60,12 @@ impl Default for App {
             pixel_format: Default::default(),
             ignore_alpha: false,
             image_format: Default::default(),
+            compressed_format: Default::default(),
+            gx_format: Default::default(),
             palette: Default::default(),
             tile: Default::default(),
+            swizzle: Default::default(),
+            bit_order: Default::default(),
             image: None,
             error: None,
             filter_method: FilterMethod::Nearest,
@@ -85,6 +99,11 @@ impl App {
                         TextInput::PaletteOffset => self.palette.offset = input,
                         TextInput::TileWidth => self.tile.width = input,
                         TextInput::TileHeight => self.tile.height = input,
+                        TextInput::PaletteCount => self.palette.count = input,
+                        TextInput::BlockWidth => self.compressed_format.block_width = input,
+                        TextInput::BlockHeight => self.compressed_format.block_height = input,
+                        TextInput::GobBlockHeight => self.swizzle.block_height = input,
+                        TextInput::Gx2Swizzle => self.swizzle.gx2_swizzle = input,
                     }
                 }
             }
@@ -94,9 +113,60 @@ impl App {
             }
             Message::OrderChanged(order) => self.pixel_format.component_order = order,
             Message::EndianChanged(endian) => self.pixel_format.endian = endian,
+            Message::CustomChanged(field, input) => {
+                if input.chars().all(char::is_numeric) || input.is_empty() {
+                    self.pixel_format.custom.set(field, input);
+                }
+            }
+            Message::IntegerChanged(option) => self.pixel_format.integer.set(option),
             Message::IgnoreAlphaChanged(val) => self.ignore_alpha = val,
-            Message::ImageFormatChanged(image_format) => self.image_format = image_format,
-            Message::PaletteBppChanged(bpp) => self.palette.bpp = bpp,
+            Message::ImageFormatChanged(image_format) => {
+                self.image_format = image_format;
+                self.bit_order = image_format.default_bit_order();
+            }
+            Message::CompressedFormatChanged(format) => self.compressed_format.selected = format,
+            Message::ReconstructZChanged(val) => self.compressed_format.reconstruct_z = val,
+            Message::ExposureChanged(val) => self.compressed_format.exposure = val,
+            Message::GxFormatChanged(format) => self.gx_format.selected = format,
+            Message::GxPaletteFormatChanged(format) => self.gx_format.palette_format = format,
+            Message::PickPaletteFile => {
+                let path = rfd::FileDialog::new().set_title("Open palette").pick_file();
+
+                if let Some(path) = path {
+                    self.palette.file_format = PaletteFile::from_path(&path);
+                    self.palette.filepath = Some(path);
+                }
+            }
+            Message::ClearPaletteFile => {
+                self.palette.filepath = None;
+                self.palette.file_format = Default::default();
+            }
+            Message::PaletteFileChanged(format) => self.palette.file_format = format,
+            Message::PaletteBppChanged(bpp) => {
+                self.palette.bpp = bpp;
+                self.palette.count = bpp.color_count().to_string();
+            }
+            Message::PalettePixelFormatChanged(pixel_format) => {
+                self.palette.pixel_format.selected = pixel_format;
+                self.palette.pixel_format.component_order = pixel_format.default_order();
+            }
+            Message::PaletteOrderChanged(order) => {
+                self.palette.pixel_format.component_order = order
+            }
+            Message::PaletteEndianChanged(endian) => self.palette.pixel_format.endian = endian,
+            Message::PaletteCustomChanged(field, input) => {
+                if input.chars().all(char::is_numeric) || input.is_empty() {
+                    self.palette.pixel_format.custom.set(field, input);
+                }
+            }
+            Message::PaletteIntegerChanged(option) => self.palette.pixel_format.integer.set(option),
+            Message::BitOrderChanged(order) => self.bit_order = order,
+            Message::Ps2ClutChanged(val) => self.palette.ps2_clut = val,
+            Message::SwizzleChanged(swizzle) => self.swizzle.selected = swizzle,
+            Message::SwapWordsChanged(val) => self.swizzle.swap_words = val,
+            Message::Gx2TileModeChanged(tile_mode) => self.swizzle.gx2_tile_mode = tile_mode,
+            Message::TilePixelOrderChanged(order) => self.tile.pixel_order = order,
+            Message::TileOrderChanged(order) => self.tile.tile_order = order,
             Message::ProcessImage => process = true,
             Message::SaveImage(format) => save = Some(format),
             Message::FilterChanged(filter_method) => {
@@ -127,6 +197,18 @@ impl App {
                 return;
             };
 
+            let indexed = if format.is_indexed() {
+                match self.read_indexed() {
+                    Ok(indexed) => Some(indexed),
+                    Err(message) => {
+                        self.error = Some(message);
+                        return;
+                    }
+                }
+            } else {
+                None
+            };
+
             let Some(path) = rfd::FileDialog::new()
                 .set_title("Save")
                 .add_filter("", format.extension())
@@ -135,19 +217,33 @@ impl App {
                 return;
             };
 
-            if let Err(message) = App::save_image(handle, format, path) {
-                self.error = Some(format!("failed to save image. {message}"));
+            let result = match indexed {
+                Some((indices, palette)) if format == SaveFormat::IndexedPng => {
+                    App::save_indexed_png(handle, &indices, &palette, self.palette.bpp, path)
+                }
+                Some((_, palette)) => App::save_palette(&palette, format, path),
+                None => App::save_image(handle, format, path),
+            };
+
+            if let Err(message) = result {
+                let kind = if format.is_palette() {
+                    "palette"
+                } else {
+                    "image"
+                };
+                self.error = Some(format!("failed to save {kind}. {message}"));
             }
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let filepath_view = self.filepath_view();
         let dim_view = self.dimension_view();
         let offset = TextInput::Offset.view("Offset:", &self.offset);
         let pixel_format_view = self.pixel_format_view();
         let image_format_view = self.image_format_view();
         let buttons_view = self.buttons_view();
+        let indexed_buttons_view = self.indexed_buttons_view();
         let error_view = self.error_view();
 
         let image_viewer = self.image_view().width(Length::Fill);
@@ -161,6 +257,7 @@ impl App {
             vertical_space(),
             Column::new()
                 .push_maybe(error_view)
+                .push_maybe(indexed_buttons_view)
                 .push(buttons_view)
                 .spacing(SPACING)
         ]
@@ -184,6 +281,21 @@ impl App {
     }
 
     fn process_image(&self) -> Result<Handle, String> {
+        let (file, width, height, offset) = self.open_image()?;
+
+        match self.image_format {
+            ImageFormat::Linear => Image::linear(self, file, width, height, offset),
+            ImageFormat::LinearIndexed | ImageFormat::TiledIndexed => {
+                Image::indexed(self, file, width, height, offset)
+            }
+            ImageFormat::Tiled => Image::tiled(self, file, width, height, offset),
+            ImageFormat::Compressed => Image::compressed(self, file, width, height, offset),
+            ImageFormat::Gx => Image::gx(self, file, width, height, offset),
+        }
+    }
+
+    /// Opens the image file and parses its width, height and offset.
+    fn open_image(&self) -> Result<(File, usize, usize, usize), String> {
         let path = self.filepath.as_deref().ok_or("file is empty")?;
         let width: usize = self.width.parse().map_err(|_| "width is empty")?;
         let height: usize = self.height.parse().map_err(|_| "height is empty")?;
@@ -195,12 +307,7 @@ impl App {
 
         let file = File::open(path).map_err(|err| err.to_string())?;
 
-        match self.image_format {
-            ImageFormat::Linear => Image::linear(self, file, width, height, offset),
-            ImageFormat::LinearIndexed => Image::linear_indexed(self, file, width, height, offset),
-            ImageFormat::Tiled => Image::tiled(self, file, width, height, offset),
-            ImageFormat::TiledIndexed => Image::tiled_indexed(self, file, width, height, offset),
-        }
+        Ok((file, width, height, offset))
     }
 
     fn save_image(handle: &Handle, format: SaveFormat, path: PathBuf) -> Result<(), String> {
@@ -218,25 +325,117 @@ impl App {
             SaveFormat::Rgba => {
                 std::fs::write(path, pixels).map_err(|err| err.kind().to_string())?;
             }
-            SaveFormat::Png => {
-                let file = std::fs::File::create(path).map_err(|err| err.kind().to_string())?;
-                let mut encoder = png::Encoder::new(file, *width, *height);
-
-                encoder.set_color(png::ColorType::Rgba);
-                encoder.set_depth(png::BitDepth::Eight);
-                encoder
-                    .write_header()
-                    .and_then(|mut wr| wr.write_image_data(pixels))
-                    .map_err(|err| err.to_string())?;
-            }
+            SaveFormat::Png => App::write_png(path, *width, *height, pixels)?,
+            _ => unreachable!(),
         };
 
         Ok(())
     }
+
+    /// Reads the indices and RGBA palette of an indexed image.
+    fn read_indexed(&self) -> Result<(Vec<usize>, Vec<u8>), String> {
+        let (file, width, height, offset) = self.open_image()?;
+
+        Image::read_indexed(self, file, width, height, offset)
+    }
+
+    /// Writes an indexed PNG at the bit depth of the indices. The palette is cut to
+    /// the entries the indices can address, or padded with transparent black up
+    /// to the largest index.
+    fn save_indexed_png(
+        handle: &Handle,
+        indices: &[usize],
+        palette: &[u8],
+        bpp: Bpp,
+        path: PathBuf,
+    ) -> Result<(), String> {
+        let Handle::Rgba { width, height, .. } = &handle else {
+            unreachable!();
+        };
+
+        let bits = bpp.bits();
+        if bits > 8 {
+            return Err("indexed PNG supports at most 8 bits per index".into());
+        }
+
+        let max_index = indices.iter().copied().max().unwrap_or_default();
+        let mut colors: Vec<_> = palette.chunks_exact(4).take(bpp.color_count()).collect();
+        colors.resize(colors.len().max(max_index + 1), &[0; 4]);
+
+        let plte: Vec<u8> = colors
+            .iter()
+            .flat_map(|color| &color[..3])
+            .copied()
+            .collect();
+        let last_translucent = colors.iter().rposition(|color| color[3] != 255);
+        let trns: Option<Vec<u8>> =
+            last_translucent.map(|last| colors[..=last].iter().map(|color| color[3]).collect());
+
+        let row_len = (*width as usize * bits).div_ceil(8);
+        let mut data = vec![0; row_len * *height as usize];
+        for (row, row_indices) in data
+            .chunks_exact_mut(row_len)
+            .zip(indices.chunks_exact(*width as usize))
+        {
+            for (x, &index) in row_indices.iter().enumerate() {
+                let bit = x * bits;
+                row[bit / 8] |= (index << (8 - bits - bit % 8)) as u8;
+            }
+        }
+
+        let depth = match bits {
+            1 => png::BitDepth::One,
+            2 => png::BitDepth::Two,
+            4 => png::BitDepth::Four,
+            _ => png::BitDepth::Eight,
+        };
+
+        let file = std::fs::File::create(path).map_err(|err| err.kind().to_string())?;
+        let mut encoder = png::Encoder::new(file, *width, *height);
+
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(depth);
+        encoder.set_palette(plte);
+        if let Some(trns) = trns {
+            encoder.set_trns(trns);
+        }
+        encoder
+            .write_header()
+            .and_then(|mut wr| wr.write_image_data(&data))
+            .map_err(|err| err.to_string())
+    }
+
+    fn save_palette(palette: &[u8], format: SaveFormat, path: PathBuf) -> Result<(), String> {
+        let data = match format {
+            SaveFormat::JascPal => palette::write_jasc_pal(palette),
+            SaveFormat::Act => palette::write_act(palette),
+            SaveFormat::Gpl => palette::write_gpl(palette),
+            SaveFormat::Swatch => {
+                let (width, height, pixels) = palette::swatch(palette);
+
+                return App::write_png(path, width as _, height as _, &pixels);
+            }
+            _ => unreachable!(),
+        };
+
+        std::fs::write(path, data).map_err(|err| err.kind().to_string())
+    }
+
+    fn write_png(path: PathBuf, width: u32, height: u32, pixels: &[u8]) -> Result<(), String> {
+        let file = std::fs::File::create(path).map_err(|err| err.kind().to_string())?;
+        let mut encoder = png::Encoder::new(file, width, height);
+
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut wr| wr.write_image_data(pixels))
+            .map_err(|err| err.to_string())
+    }
 }
 
 impl App {
-    pub fn filepath_view(&self) -> Row<Message> {
+    pub fn filepath_view(&self) -> Row<'_, Message> {
         use iced::widget::text_input::Status;
 
         let path = self
@@ -256,7 +455,7 @@ impl App {
             .align_y(Vertical::Center)
     }
 
-    pub fn dimension_view(&self) -> Row<Message> {
+    pub fn dimension_view(&self) -> Row<'_, Message> {
         row![
             TextInput::Width.view("Width:", &self.width),
             TextInput::Height.view("Height:", &self.height),
@@ -264,10 +463,31 @@ impl App {
         .spacing(SPACING)
     }
 
-    pub fn pixel_format_view(&self) -> Column<Message> {
-        let row = self.pixel_format.view();
+    pub fn pixel_format_view(&self) -> Column<'_, Message> {
+        let (row, use_alpha): (Option<Element<Message>>, bool) = match self.image_format {
+            ImageFormat::Compressed => (
+                Some(self.compressed_format.view().into()),
+                self.compressed_format.selected.use_alpha(),
+            ),
+            ImageFormat::Gx => (
+                Some(self.gx_format.view().into()),
+                self.gx_format.selected.use_alpha(),
+            ),
+            // Parsed palette files define their own colors
+            ImageFormat::LinearIndexed | ImageFormat::TiledIndexed if self.palette.is_parsed() => {
+                (None, true)
+            }
+            ImageFormat::LinearIndexed | ImageFormat::TiledIndexed => (
+                Some(self.palette.pixel_format.view().into()),
+                self.palette.pixel_format.selected.use_alpha(),
+            ),
+            _ => (
+                Some(self.pixel_format.view().into()),
+                self.pixel_format.selected.use_alpha(),
+            ),
+        };
 
-        let checkbox: Option<Checkbox<Message>> = if self.pixel_format.selected.use_alpha() {
+        let checkbox: Option<Checkbox<Message>> = if use_alpha {
             checkbox("Ignore alpha", self.ignore_alpha)
                 .on_toggle(Message::IgnoreAlphaChanged)
                 .into()
@@ -275,28 +495,88 @@ impl App {
             None
         };
 
-        column![row].spacing(SPACING).push_maybe(checkbox)
+        Column::new()
+            .push_maybe(row)
+            .push_maybe(checkbox)
+            .spacing(SPACING)
     }
 
-    pub fn image_format_view(&self) -> Column<Message> {
+    pub fn image_format_view(&self) -> Column<'_, Message> {
         let image_format_view = self.image_format.view();
 
         let view: Option<Element<Message>> = match self.image_format {
-            ImageFormat::Linear => None,
-            ImageFormat::LinearIndexed => self.palette.view().into(),
-            ImageFormat::Tiled => self.tile.view().into(),
+            ImageFormat::Linear => Some(self.swizzle.view(self.height.parse().ok()).into()),
+            ImageFormat::Compressed if self.compressed_format.selected.can_swizzle() => {
+                let block_h = self
+                    .compressed_format
+                    .block_size()
+                    .map(|(_, block_h)| block_h);
+                let height = self.height.parse::<usize>().ok().zip(block_h.ok());
+                let rows = height.map(|(height, block_h)| height.div_ceil(block_h));
+
+                Some(self.swizzle.view(rows).into())
+            }
+            ImageFormat::Compressed => None,
+            ImageFormat::LinearIndexed => {
+                let swizzle_view = self.swizzle.view(self.height.parse().ok());
+                let pal_view = self.palette.view();
+
+                Some(column![swizzle_view, pal_view].spacing(SPACING).into())
+            }
+            ImageFormat::Tiled => {
+                let swizzle_view = self.swizzle.view(self.height.parse().ok());
+                let tile_view = self.tile.view();
+
+                Some(column![swizzle_view, tile_view].spacing(SPACING).into())
+            }
             ImageFormat::TiledIndexed => {
+                let swizzle_view = self.swizzle.view(self.height.parse().ok());
                 let tile_view = self.tile.view();
                 let pal_view = self.palette.view();
 
-                Some(column![tile_view, pal_view].spacing(SPACING).into())
+                Some(
+                    column![swizzle_view, tile_view, pal_view]
+                        .spacing(SPACING)
+                        .into(),
+                )
+            }
+            ImageFormat::Gx if self.gx_format.selected.is_paletted() => {
+                Some(self.palette.source_view().into())
             }
+            ImageFormat::Gx => None,
         };
+        let bit_order_view = self.has_sub_byte_pixels().then(|| self.bit_order.view());
+        // The image pixel format is hidden for indexed images, so show its
+        // endianness here for 16-bit indices
+        let is_indexed = matches!(
+            self.image_format,
+            ImageFormat::LinearIndexed | ImageFormat::TiledIndexed
+        );
+        let index_endian_view = (is_indexed && self.palette.bpp == Bpp::Bpp16)
+            .then(|| self.pixel_format.endian.view(Message::EndianChanged));
 
-        column![image_format_view].push_maybe(view).spacing(SPACING)
+        column![image_format_view]
+            .push_maybe(view)
+            .push_maybe(bit_order_view)
+            .push_maybe(index_endian_view)
+            .spacing(SPACING)
     }
 
-    pub fn buttons_view(&self) -> Row<Message> {
+    /// Whether pixels, palette indices or palette colors are narrower than a
+    /// byte, and so packed in the bit order.
+    fn has_sub_byte_pixels(&self) -> bool {
+        match self.image_format {
+            ImageFormat::Linear | ImageFormat::Tiled => self.pixel_format.is_sub_byte(),
+            ImageFormat::LinearIndexed | ImageFormat::TiledIndexed => {
+                self.palette.bpp.bits() < 8
+                    || (!self.palette.is_parsed() && self.palette.pixel_format.is_sub_byte())
+            }
+            ImageFormat::Compressed => false,
+            ImageFormat::Gx => self.gx_format.selected.has_nibbles(),
+        }
+    }
+
+    pub fn buttons_view(&self) -> Row<'_, Message> {
         let rgba_save = button("Save (rgba)")
             .on_press(Message::SaveImage(SaveFormat::Rgba))
             .style(button::secondary);
@@ -308,7 +588,40 @@ impl App {
         row![process, horizontal_space(), rgba_save, png_save].spacing(SPACING)
     }
 
-    pub fn image_view(&self) -> Stack<Message> {
+    /// Indexed PNG and palette export buttons, shown for indexed images.
+    pub fn indexed_buttons_view(&self) -> Option<Column<'_, Message>> {
+        if !matches!(
+            self.image_format,
+            ImageFormat::LinearIndexed | ImageFormat::TiledIndexed
+        ) {
+            return None;
+        }
+
+        let save_button = |label, format| {
+            button(label)
+                .on_press(Message::SaveImage(format))
+                .style(button::secondary)
+        };
+
+        let png_row = row![
+            horizontal_space(),
+            save_button("Save (indexed png)", SaveFormat::IndexedPng).style(button::success),
+        ];
+        let palette_row = row![
+            text("Palette:"),
+            horizontal_space(),
+            save_button(".pal", SaveFormat::JascPal),
+            save_button(".act", SaveFormat::Act),
+            save_button(".gpl", SaveFormat::Gpl),
+            save_button("swatch", SaveFormat::Swatch),
+        ]
+        .spacing(SPACING)
+        .align_y(Vertical::Center);
+
+        Some(column![png_row, palette_row].spacing(SPACING))
+    }
+
+    pub fn image_view(&self) -> Stack<'_, Message> {
         fn style(theme: &iced::Theme) -> container::Style {
             let color = iced::Color {
                 r: 0.0,
@@ -345,7 +658,7 @@ impl App {
         stack([container.into(), filter_view.into()])
     }
 
-    fn filter_view(&self) -> Container<Message> {
+    fn filter_view(&self) -> Container<'_, Message> {
         let linear = radio(
             "Linear",
             FilterMethod::Linear,
@@ -370,7 +683,7 @@ impl App {
             .align_x(Horizontal::Center)
     }
 
-    fn error_view(&self) -> Option<Row<Message>> {
+    fn error_view(&self) -> Option<Row<'_, Message>> {
         let message = self.error.as_ref()?;
 
         Some(row![text(message).style(iced::widget::text::danger)].spacing(SPACING))
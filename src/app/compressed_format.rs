@@ -0,0 +1,276 @@
+use iced::{
+    alignment::Vertical,
+    widget::{Checkbox, Column, Row, checkbox, column, combo_box, row, slider, text},
+};
+
+use super::message::{Message, TextInput};
+use crate::{LABEL_WIDTH, SPACING};
+use CompressedFormat::*;
+
+/// Block footprints allowed for 2D ASTC textures.
+const ASTC_FOOTPRINTS: [(usize, usize); 14] = [
+    (4, 4),
+    (5, 4),
+    (5, 5),
+    (6, 5),
+    (6, 6),
+    (8, 5),
+    (8, 6),
+    (8, 8),
+    (10, 5),
+    (10, 6),
+    (10, 8),
+    (10, 10),
+    (12, 10),
+    (12, 12),
+];
+
+#[derive(Debug)]
+pub struct CompressedFormatState {
+    pub state: combo_box::State<CompressedFormat>,
+    pub selected: CompressedFormat,
+    pub reconstruct_z: bool,
+    pub exposure: f32,
+    pub block_width: String,
+    pub block_height: String,
+}
+
+impl Default for CompressedFormatState {
+    fn default() -> Self {
+        Self {
+            state: combo_box::State::new(CompressedFormat::all()),
+            selected: Default::default(),
+            reconstruct_z: false,
+            exposure: 0.0,
+            block_width: 4.to_string(),
+            block_height: 4.to_string(),
+        }
+    }
+}
+
+impl CompressedFormatState {
+    pub fn view(&self) -> Column<'_, Message> {
+        let label = text("Format:").width(LABEL_WIDTH);
+        let combo_box = combo_box(
+            &self.state,
+            "",
+            Some(&self.selected),
+            Message::CompressedFormatChanged,
+        )
+        .width(160);
+
+        let row = row![label, combo_box]
+            .spacing(SPACING)
+            .align_y(Vertical::Center);
+
+        let reconstruct_z: Option<Checkbox<Message>> = if self.selected.is_normal_map() {
+            checkbox("Reconstruct Z", self.reconstruct_z)
+                .on_toggle(Message::ReconstructZChanged)
+                .into()
+        } else {
+            None
+        };
+
+        let exposure: Option<Row<Message>> = if self.selected.is_hdr() {
+            let label = text("Exposure:").width(LABEL_WIDTH);
+            let slider = slider(-10.0..=10.0, self.exposure, Message::ExposureChanged)
+                .step(0.25)
+                .width(120);
+
+            row![label, slider, text(format!("{:+.2} EV", self.exposure))]
+                .spacing(SPACING)
+                .align_y(Vertical::Center)
+                .into()
+        } else {
+            None
+        };
+
+        let footprint: Option<Row<Message>> = if self.selected.has_footprint() {
+            let width = TextInput::BlockWidth.view("Block width:", &self.block_width);
+            let height = TextInput::BlockHeight.view("Block height:", &self.block_height);
+
+            row![width, height].spacing(SPACING).into()
+        } else {
+            None
+        };
+
+        column![row]
+            .push_maybe(footprint)
+            .push_maybe(reconstruct_z)
+            .push_maybe(exposure)
+            .spacing(SPACING)
+    }
+
+    /// Block size of the selected format, read from the block width and height
+    /// fields for formats with a selectable footprint.
+    pub fn block_size(&self) -> Result<(usize, usize), String> {
+        if !self.selected.has_footprint() {
+            return Ok(self.selected.block_size());
+        }
+
+        let block_w: usize = self
+            .block_width
+            .parse()
+            .map_err(|_| "block width is empty")?;
+        let block_h: usize = self
+            .block_height
+            .parse()
+            .map_err(|_| "block height is empty")?;
+
+        if !ASTC_FOOTPRINTS.contains(&(block_w, block_h)) {
+            return Err(format!("{block_w}x{block_h} is not a valid ASTC footprint"));
+        }
+
+        Ok((block_w, block_h))
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompressedFormat {
+    #[default]
+    BC1,
+    BC2,
+    BC3,
+    BC4U,
+    BC4S,
+    BC5U,
+    BC5S,
+    BC6HU,
+    BC6HS,
+    BC7,
+    ETC1,
+    ETC2RGB,
+    ETC2RGBA1,
+    ETC2RGBA8,
+    ETC1N3DS,
+    ETC1A4N3DS,
+    EACR11U,
+    EACR11S,
+    EACRG11U,
+    EACRG11S,
+    ATCRGB,
+    ATCRGBAExplicit,
+    ATCRGBAInterpolated,
+    ASTC,
+    PVRTC2BPP,
+    PVRTC4BPP,
+}
+
+impl CompressedFormat {
+    fn all() -> Vec<Self> {
+        vec![
+            BC1,
+            BC2,
+            BC3,
+            BC4U,
+            BC4S,
+            BC5U,
+            BC5S,
+            BC6HU,
+            BC6HS,
+            BC7,
+            ETC1,
+            ETC2RGB,
+            ETC2RGBA1,
+            ETC2RGBA8,
+            ETC1N3DS,
+            ETC1A4N3DS,
+            EACR11U,
+            EACR11S,
+            EACRG11U,
+            EACRG11S,
+            ATCRGB,
+            ATCRGBAExplicit,
+            ATCRGBAInterpolated,
+            ASTC,
+            PVRTC2BPP,
+            PVRTC4BPP,
+        ]
+    }
+
+    pub fn use_alpha(&self) -> bool {
+        matches!(
+            self,
+            BC1 | BC2
+                | BC3
+                | BC7
+                | ETC2RGBA1
+                | ETC2RGBA8
+                | ETC1A4N3DS
+                | ATCRGBAExplicit
+                | ATCRGBAInterpolated
+                | ASTC
+                | PVRTC2BPP
+                | PVRTC4BPP
+        )
+    }
+
+    pub fn is_normal_map(&self) -> bool {
+        matches!(self, BC5U | BC5S | EACRG11U | EACRG11S)
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, BC6HU | BC6HS)
+    }
+
+    /// PVRTC blends neighboring blocks, so it's decoded as a whole image. Only
+    /// PVRTC1 is supported, not PVRTC2.
+    pub fn is_pvrtc(&self) -> bool {
+        matches!(self, PVRTC2BPP | PVRTC4BPP)
+    }
+
+    /// 3DS textures group blocks into 8x8 Morton-ordered tiles and are stored
+    /// upside-down.
+    pub fn is_3ds(&self) -> bool {
+        matches!(self, ETC1N3DS | ETC1A4N3DS)
+    }
+
+    /// Formats with their own block order can't be combined with a swizzle.
+    pub fn can_swizzle(&self) -> bool {
+        !self.is_pvrtc() && !self.is_3ds()
+    }
+
+    pub fn has_footprint(&self) -> bool {
+        matches!(self, ASTC)
+    }
+
+    /// Fixed block size of the format. Formats with a selectable footprint report
+    /// their smallest one.
+    fn block_size(&self) -> (usize, usize) {
+        match self {
+            BC1 | BC2 | BC3 | BC4U | BC4S | BC5U | BC5S | BC6HU | BC6HS | BC7 => (4, 4),
+            ETC1 | ETC2RGB | ETC2RGBA1 | ETC2RGBA8 | ETC1N3DS | ETC1A4N3DS | EACR11U | EACR11S
+            | EACRG11U | EACRG11S => (4, 4),
+            ATCRGB | ATCRGBAExplicit | ATCRGBAInterpolated => (4, 4),
+            ASTC => (4, 4),
+            PVRTC2BPP => (8, 4),
+            PVRTC4BPP => (4, 4),
+        }
+    }
+
+    pub fn bytes_per_block(&self) -> usize {
+        match self {
+            BC1 | BC4U | BC4S => 8,
+            BC2 | BC3 | BC5U | BC5S | BC6HU | BC6HS | BC7 => 16,
+            ETC1 | ETC2RGB | ETC2RGBA1 | ETC1N3DS | EACR11U | EACR11S => 8,
+            ETC2RGBA8 | ETC1A4N3DS | EACRG11U | EACRG11S => 16,
+            ATCRGB => 8,
+            ATCRGBAExplicit | ATCRGBAInterpolated => 16,
+            ASTC => 16,
+            PVRTC2BPP | PVRTC4BPP => 8,
+        }
+    }
+}
+
+impl std::fmt::Display for CompressedFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ETC1N3DS => write!(f, "ETC1 (3DS)"),
+            ETC1A4N3DS => write!(f, "ETC1A4 (3DS)"),
+            PVRTC2BPP => write!(f, "PVRTC1 2bpp"),
+            PVRTC4BPP => write!(f, "PVRTC1 4bpp"),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use iced::{
+    alignment::Vertical,
+    widget::{Column, Row, column, combo_box, row, text},
+};
+
+use super::message::Message;
+use crate::{LABEL_WIDTH, SPACING};
+use GxFormat::*;
+
+#[derive(Debug)]
+pub struct GxFormatState {
+    pub state: combo_box::State<GxFormat>,
+    pub selected: GxFormat,
+    pub palette_state: combo_box::State<GxPaletteFormat>,
+    pub palette_format: GxPaletteFormat,
+}
+
+impl Default for GxFormatState {
+    fn default() -> Self {
+        Self {
+            state: combo_box::State::new(GxFormat::all()),
+            selected: Default::default(),
+            palette_state: combo_box::State::new(GxPaletteFormat::all()),
+            palette_format: Default::default(),
+        }
+    }
+}
+
+impl GxFormatState {
+    pub fn view(&self) -> Column<'_, Message> {
+        let palette: Option<Row<Message>> = if self.selected.is_paletted() {
+            let label = text("Palette:").width(LABEL_WIDTH);
+            let combo_box = combo_box(
+                &self.palette_state,
+                "",
+                Some(&self.palette_format),
+                Message::GxPaletteFormatChanged,
+            )
+            .width(80);
+
+            row![label, combo_box]
+                .spacing(SPACING)
+                .align_y(Vertical::Center)
+                .into()
+        } else {
+            None
+        };
+
+        let label = text("Format:").width(LABEL_WIDTH);
+        let combo_box = combo_box(
+            &self.state,
+            "",
+            Some(&self.selected),
+            Message::GxFormatChanged,
+        )
+        .width(80);
+
+        let row = row![label, combo_box]
+            .spacing(SPACING)
+            .align_y(Vertical::Center);
+
+        column![row].push_maybe(palette).spacing(SPACING)
+    }
+}
+
+/// Texture formats of the GameCube and Wii GX GPU. Each one stores its pixels in
+/// blocks of a fixed size, in row-major order.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GxFormat {
+    #[default]
+    I4,
+    I8,
+    IA4,
+    IA8,
+    RGB565,
+    RGB5A3,
+    RGBA32,
+    C4,
+    C8,
+    C14X2,
+    CMPR,
+}
+
+impl GxFormat {
+    fn all() -> Vec<Self> {
+        vec![
+            I4, I8, IA4, IA8, RGB565, RGB5A3, RGBA32, C4, C8, C14X2, CMPR,
+        ]
+    }
+
+    pub fn use_alpha(&self) -> bool {
+        !matches!(self, RGB565)
+    }
+
+    /// Formats with two pixels per byte.
+    pub fn has_nibbles(&self) -> bool {
+        matches!(self, I4 | C4)
+    }
+
+    pub fn is_paletted(&self) -> bool {
+        matches!(self, C4 | C8 | C14X2)
+    }
+
+    pub fn color_count(&self) -> usize {
+        match self {
+            C4 => 16,
+            C8 => 256,
+            C14X2 => 16384,
+            _ => 0,
+        }
+    }
+
+    pub fn block_size(&self) -> (usize, usize) {
+        match self {
+            I4 | C4 | CMPR => (8, 8),
+            I8 | IA4 | C8 => (8, 4),
+            IA8 | RGB565 | RGB5A3 | RGBA32 | C14X2 => (4, 4),
+        }
+    }
+
+    pub fn bytes_per_block(&self) -> usize {
+        match self {
+            RGBA32 => 64,
+            _ => 32,
+        }
+    }
+}
+
+impl std::fmt::Display for GxFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GxPaletteFormat {
+    IA8,
+    RGB565,
+    #[default]
+    RGB5A3,
+}
+
+impl GxPaletteFormat {
+    fn all() -> Vec<Self> {
+        vec![Self::IA8, Self::RGB565, Self::RGB5A3]
+    }
+}
+
+impl std::fmt::Display for GxPaletteFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
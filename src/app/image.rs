@@ -1,13 +1,26 @@
 use std::{
     fs::File,
     io::{SeekFrom::Start, prelude::*},
-    slice::ChunksExact,
 };
 
 use iced::widget::image::Handle;
 
-use super::pixel_format::{Endian, PixelFormat};
-use super::{App, image_format::Bpp};
+use super::App;
+use super::compressed_format::CompressedFormat;
+use super::gx_format::{GxFormat, GxPaletteFormat};
+use super::image_format::{BitOrder, Bpp, ImageFormat};
+use super::pixel_format::{Endian, PixelFormat, PixelFormatState};
+
+mod astc;
+mod atc;
+mod bc;
+mod bc6h;
+mod bc7;
+mod etc;
+mod gx;
+pub mod palette;
+mod pvrtc;
+mod swizzle;
 
 pub struct Image;
 
@@ -23,78 +36,41 @@ impl Image {
         h: usize,
         offset: usize,
     ) -> Result<Handle, String> {
-        let pixel_format = app.pixel_format.selected;
-        let pixel_count = w * h;
-        let bytes_per_pixel = pixel_format.bytes_per_pixel();
+        let bits = app.pixel_format.bits_per_pixel()?;
+        let positions = swizzle::positions(&app.swizzle, w, h, bits)?;
+        let pixel_data = read_pixels(
+            &mut file,
+            offset,
+            w * h,
+            bits,
+            app.bit_order,
+            app.swizzle.swaps_words(),
+            positions,
+        )?;
 
-        let mut pixel_data = vec![0; pixel_count * bytes_per_pixel];
-        file.seek(Start(offset as _))
-            .map_err(|err| err.to_string())?;
-        file.read_exact(&mut pixel_data)
-            .map_err(|err| format!("failed to fill pixel data buffer. {}", err.kind()))?;
-
-        let pixel_chunks = pixel_data.chunks_exact(bytes_per_pixel);
         let mut rgba = vec![0; w * h * 4];
-        fill_rgba(app, &mut rgba, pixel_chunks)?;
+        fill_rgba(
+            &app.pixel_format,
+            app.ignore_alpha,
+            app.bit_order,
+            &mut rgba,
+            &pixel_data,
+        )?;
 
         Ok(Self::new_handle(w as _, h as _, rgba))
     }
 
-    pub fn linear_indexed(
+    pub fn indexed(
         app: &App,
-        mut file: File,
+        file: File,
         w: usize,
         h: usize,
         offset: usize,
     ) -> Result<Handle, String> {
-        let palette = &app.palette;
-        let palette_offset = palette.offset().map_err(|_| "palette offset is empty")?;
-
-        let pixel_format = app.pixel_format.selected;
-        let color_count = palette.color_count();
-        let bytes_per_color = pixel_format.bytes_per_pixel();
-
-        let mut palette_data = vec![0; color_count * bytes_per_color];
-        file.seek(Start(palette_offset as _))
-            .map_err(|err| err.to_string())?;
-        file.read_exact(&mut palette_data)
-            .map_err(|err| format!("failed to fill palette data buffer. {}", err.kind()))?;
-
-        let color_chunks = palette_data.chunks_exact(bytes_per_color);
-        let mut palette_rgba = vec![0; color_count * 4];
-        fill_rgba(app, &mut palette_rgba, color_chunks)?;
-
-        let mut pixel_data = match palette.bpp {
-            Bpp::Bpp4 => vec![0; w * h / 2],
-            Bpp::Bpp8 => vec![0; w * h],
-        };
-        file.seek(Start(offset as _))
-            .map_err(|err| err.to_string())?;
-        file.read_exact(&mut pixel_data)
-            .map_err(|err| format!("failed to fill pixel data buffer. {}", err.kind()))?;
+        let (indices, palette_rgba) = Self::read_indexed(app, file, w, h, offset)?;
 
         let mut rgba = vec![0; w * h * 4];
-        match palette.bpp {
-            Bpp::Bpp4 => {
-                for (i, pixels) in pixel_data.into_iter().enumerate() {
-                    let src1 = (pixels & 0xF) as usize * 4;
-                    let src2 = ((pixels >> 4) & 0xF) as usize * 4;
-                    let dst1 = i * 2 * 4;
-                    let dst2 = dst1 + 4;
-
-                    rgba[dst1..dst1 + 4].clone_from_slice(&palette_rgba[src1..src1 + 4]);
-                    rgba[dst2..dst2 + 4].clone_from_slice(&palette_rgba[src2..src2 + 4]);
-                }
-            }
-            Bpp::Bpp8 => {
-                for (i, pixel) in pixel_data.into_iter().enumerate() {
-                    let src = pixel as usize * 4;
-                    let dst = i * 4;
-
-                    rgba[dst..dst + 4].clone_from_slice(&palette_rgba[src..src + 4]);
-                }
-            }
-        }
+        fill_indexed(&mut rgba, &indices, &palette_rgba);
 
         Ok(Self::new_handle(w as _, h as _, rgba))
     }
@@ -106,195 +82,641 @@ impl Image {
         h: usize,
         offset: usize,
     ) -> Result<Handle, String> {
+        let bits = app.pixel_format.bits_per_pixel()?;
+        let positions = Self::tile_positions(app, w, h, bits)?;
+        let pixel_data = read_pixels(
+            &mut file,
+            offset,
+            w * h,
+            bits,
+            app.bit_order,
+            app.swizzle.swaps_words(),
+            Some(positions),
+        )?;
+
+        let mut rgba = vec![0; w * h * 4];
+        fill_rgba(
+            &app.pixel_format,
+            app.ignore_alpha,
+            app.bit_order,
+            &mut rgba,
+            &pixel_data,
+        )?;
+
+        Ok(Self::new_handle(w as _, h as _, rgba))
+    }
+
+    /// Position of each pixel of a tiled image in its data, in row-major order. A
+    /// swizzle applies to the tiled data as a whole, so it is undone first.
+    fn tile_positions(app: &App, w: usize, h: usize, bits: usize) -> Result<Vec<usize>, String> {
         let tile_w = app.tile.width().map_err(|_| "tile width is empty")?;
         let tile_h = app.tile.height().map_err(|_| "tile height is empty")?;
 
-        if w % tile_w != 0 {
+        if !w.is_multiple_of(tile_w) {
             return Err("width is not divisible by tile width".to_owned());
         }
-        if h % tile_h != 0 {
+        if !h.is_multiple_of(tile_h) {
             return Err("height is not divisible by tile height".to_owned());
         }
 
-        let tile_row = w / tile_w;
-        let tile_col = h / tile_h;
-        let tile_count = tile_row * tile_col;
-
-        let mut tiles = Vec::with_capacity(tile_count);
+        let positions = swizzle::tiled(
+            w,
+            h,
+            (tile_w, tile_h),
+            app.tile.pixel_order,
+            app.tile.tile_order,
+        )?;
+
+        match swizzle::positions(&app.swizzle, w, h, bits)? {
+            Some(swizzled) => positions
+                .into_iter()
+                .map(|position| swizzled.get(position).copied())
+                .collect::<Option<_>>()
+                .ok_or_else(|| "swizzle needs tiles without gaps between them".to_owned()),
+            None => Ok(positions),
+        }
+    }
 
-        let pixel_format = app.pixel_format.selected;
-        let pixel_count = tile_w * tile_h;
-        let bytes_per_pixel = pixel_format.bytes_per_pixel();
+    /// Reads the indices of an indexed image in row-major order, unswizzling and
+    /// untiling them, along with its RGBA palette.
+    pub fn read_indexed(
+        app: &App,
+        mut file: File,
+        w: usize,
+        h: usize,
+        offset: usize,
+    ) -> Result<(Vec<usize>, Vec<u8>), String> {
+        let palette = &app.palette;
+        let bits = palette.bpp.bits();
+        let positions = if app.image_format == ImageFormat::TiledIndexed {
+            Some(Self::tile_positions(app, w, h, bits)?)
+        } else {
+            swizzle::positions(&app.swizzle, w, h, bits)?
+        };
 
-        let mut pixel_datas = vec![0; pixel_count * bytes_per_pixel * tile_count];
-        file.seek(Start(offset as _))
-            .map_err(|err| err.to_string())?;
-        file.read_exact(&mut pixel_datas)
-            .map_err(|err| format!("failed to fill pixel data buffer. {}", err.kind()))?;
+        let palette_rgba = Self::read_palette(app, &mut file)?;
+
+        let pixel_data = read_pixels(
+            &mut file,
+            offset,
+            w * h,
+            bits,
+            app.bit_order,
+            app.swizzle.swaps_words(),
+            positions,
+        )?;
+
+        let indices = unpack_indices(
+            &pixel_data,
+            palette.bpp,
+            app.bit_order,
+            app.pixel_format.endian,
+            w * h,
+        );
+
+        Ok((indices, palette_rgba))
+    }
 
-        for pixel_data in pixel_datas.chunks_exact(pixel_count * bytes_per_pixel) {
-            let mut tile_rgba = vec![0; tile_w * tile_h * 4];
-            let chunks = pixel_data.chunks_exact(bytes_per_pixel);
+    /// Reads and decodes the palette with its own count and pixel format, from the
+    /// image file or a separate palette file.
+    fn read_palette(app: &App, file: &mut File) -> Result<Vec<u8>, String> {
+        let palette = &app.palette;
+        let bits_per_color = palette.pixel_format.bits_per_pixel()?;
+
+        let (palette_data, color_count) =
+            match Self::read_palette_data(app, file, bits_per_color, usize::MAX)? {
+                PaletteData::Parsed(mut palette_rgba) => {
+                    if app.ignore_alpha {
+                        palette_rgba
+                            .chunks_exact_mut(4)
+                            .for_each(|color| color[3] = 255);
+                    }
 
-            fill_rgba(app, &mut tile_rgba, chunks)?;
+                    return Ok(palette_rgba);
+                }
+                PaletteData::Raw(data, count)
+                    if data.len() < (count * bits_per_color).div_ceil(8) =>
+                {
+                    return Err("failed to fill palette data buffer. unexpected end of file".into());
+                }
+                PaletteData::Raw(data, count) => (data, count),
+            };
 
-            tiles.push(tile_rgba);
+        let mut palette_rgba = vec![0; color_count * 4];
+        fill_rgba(
+            &palette.pixel_format,
+            app.ignore_alpha,
+            app.bit_order,
+            &mut palette_rgba,
+            &palette_data,
+        )?;
+
+        if palette.ps2_clut {
+            swizzle::ps2_clut(&mut palette_rgba);
         }
 
-        let mut rgba = vec![0; w * h * 4];
+        Ok(palette_rgba)
+    }
+
+    /// Reads the palette from the palette file, or from the image file when none
+    /// is picked. Raw palettes are read at the palette offset, as many entries of
+    /// `bits_per_color` as the palette count asks for up to `max_count`, fewer if
+    /// the file ends first.
+    fn read_palette_data(
+        app: &App,
+        file: &mut File,
+        bits_per_color: usize,
+        max_count: usize,
+    ) -> Result<PaletteData, String> {
+        let palette = &app.palette;
+        let mut palette_file = match palette.filepath.as_deref() {
+            Some(path) => Some(File::open(path).map_err(|err| err.to_string())?),
+            None => None,
+        };
+        let file = palette_file.as_mut().unwrap_or(file);
 
-        for y in 0..h {
-            for x in 0..w {
-                let tile_x = x / tile_w;
-                let tile_y = y / tile_h;
-                let tile = &tiles[tile_y * tile_row + tile_x];
+        if palette.is_parsed() {
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)
+                .map_err(|err| format!("failed to read palette file. {}", err.kind()))?;
 
-                let src = ((y % tile_h) * tile_w + (x % tile_w)) * 4;
-                let dst = (y * w + x) * 4;
+            return Ok(PaletteData::Parsed(palette::parse(
+                &data,
+                palette.file_format,
+            )?));
+        }
 
-                rgba[dst..dst + 4].copy_from_slice(&tile[src..src + 4]);
-            }
+        let palette_offset = palette.offset().map_err(|_| "palette offset is empty")?;
+        let count = palette
+            .color_count()
+            .map_err(|_| "palette count is empty")?
+            .min(max_count);
+
+        if count == 0 {
+            return Err("palette count cannot be zero".into());
         }
 
-        Ok(Self::new_handle(w as _, h as _, rgba))
+        let len = (count * bits_per_color).div_ceil(8);
+        let mut data = Vec::with_capacity(len);
+        file.seek(Start(palette_offset as _))
+            .map_err(|err| err.to_string())?;
+        file.take(len as _)
+            .read_to_end(&mut data)
+            .map_err(|err| format!("failed to fill palette data buffer. {}", err.kind()))?;
+
+        Ok(PaletteData::Raw(data, count))
     }
 
-    pub fn tiled_indexed(
+    pub fn compressed(
         app: &App,
         mut file: File,
         w: usize,
         h: usize,
         offset: usize,
     ) -> Result<Handle, String> {
-        let palette = &app.palette;
-        let palette_offset = palette.offset().map_err(|_| "palette offset is empty")?;
-        let pixel_format = app.pixel_format.selected;
-        let color_count = palette.color_count();
-        let bytes_per_color = pixel_format.bytes_per_pixel();
+        let format = app.compressed_format.selected;
+        let (block_w, block_h) = app.compressed_format.block_size()?;
 
-        let tile_w = app.tile.width().map_err(|_| "tile width is empty")?;
-        let tile_h = app.tile.height().map_err(|_| "tile height is empty")?;
-        if w % tile_w != 0 {
-            return Err("width is not divisible by tile width".to_owned());
-        }
-        if h % tile_h != 0 {
-            return Err("height is not divisible by tile height".to_owned());
+        if format.is_3ds() {
+            if !w.is_multiple_of(8) {
+                return Err("width is not divisible by 3DS tile width".to_owned());
+            }
+            if !h.is_multiple_of(8) {
+                return Err("height is not divisible by 3DS tile height".to_owned());
+            }
         }
 
-        let tile_row = w / tile_w;
-        let tile_col = h / tile_h;
-        let tile_count = tile_row * tile_col;
-        let tile_pixel_count = tile_w * tile_h;
-
-        let mut palette_data = vec![0; color_count * bytes_per_color];
-        file.seek(Start(palette_offset as _))
-            .map_err(|err| err.to_string())?;
-        file.read_exact(&mut palette_data)
-            .map_err(|err| format!("failed to fill palette data buffer. {}", err.kind()))?;
+        let (blocks_x, blocks_y) = if format.is_pvrtc() {
+            pvrtc::block_count(w, h, block_w, block_h)
+        } else {
+            (w.div_ceil(block_w), h.div_ceil(block_h))
+        };
 
-        let color_chunks = palette_data.chunks_exact(bytes_per_color);
-        let mut palette_rgba = vec![0; color_count * 4];
-        fill_rgba(app, &mut palette_rgba, color_chunks)?;
+        let bits = format.bytes_per_block() * 8;
+        let (positions, swap_words) = if format.can_swizzle() {
+            (
+                swizzle::positions(&app.swizzle, blocks_x, blocks_y, bits)?,
+                app.swizzle.swaps_words(),
+            )
+        } else {
+            (None, false)
+        };
+        let block_data = read_pixels(
+            &mut file,
+            offset,
+            blocks_x * blocks_y,
+            bits,
+            app.bit_order,
+            swap_words,
+            positions,
+        )?;
+
+        let reconstruct_z = app.compressed_format.reconstruct_z;
+        let exposure = app.compressed_format.exposure;
+        let decode: &dyn Fn(&[u8], &mut [u8]) = match format {
+            CompressedFormat::BC1 => &bc::bc1,
+            CompressedFormat::BC2 => &bc::bc2,
+            CompressedFormat::BC3 => &bc::bc3,
+            CompressedFormat::BC4U => &|block, rgba| bc::bc4(block, rgba, false),
+            CompressedFormat::BC4S => &|block, rgba| bc::bc4(block, rgba, true),
+            CompressedFormat::BC5U => &|block, rgba| bc::bc5(block, rgba, false, reconstruct_z),
+            CompressedFormat::BC5S => &|block, rgba| bc::bc5(block, rgba, true, reconstruct_z),
+            CompressedFormat::BC6HU => {
+                &|block, rgba| tone_map(&bc6h::bc6h(block, false), exposure, rgba)
+            }
+            CompressedFormat::BC6HS => {
+                &|block, rgba| tone_map(&bc6h::bc6h(block, true), exposure, rgba)
+            }
+            CompressedFormat::BC7 => &bc7::bc7,
+            CompressedFormat::ETC1 => &etc::etc1,
+            CompressedFormat::ETC2RGB => &etc::etc2_rgb,
+            CompressedFormat::ETC2RGBA1 => &etc::etc2_rgba1,
+            CompressedFormat::ETC2RGBA8 => &etc::etc2_rgba8,
+            CompressedFormat::ETC1N3DS | CompressedFormat::ETC1A4N3DS => {
+                let decode = match format {
+                    CompressedFormat::ETC1A4N3DS => etc::etc1a4_3ds,
+                    _ => etc::etc1_3ds,
+                };
+                let rgba = decode_3ds(&block_data, w, h, format.bytes_per_block(), decode);
 
-        let mut pixel_datas = match palette.bpp {
-            Bpp::Bpp4 => vec![0; w * h / 2],
-            Bpp::Bpp8 => vec![0; w * h],
+                return Ok(Self::finish_blocks(app, w, h, rgba));
+            }
+            CompressedFormat::EACR11U => &|block, rgba| etc::eac_r11(block, rgba, false),
+            CompressedFormat::EACR11S => &|block, rgba| etc::eac_r11(block, rgba, true),
+            CompressedFormat::EACRG11U => {
+                &|block, rgba| etc::eac_rg11(block, rgba, false, reconstruct_z)
+            }
+            CompressedFormat::EACRG11S => {
+                &|block, rgba| etc::eac_rg11(block, rgba, true, reconstruct_z)
+            }
+            CompressedFormat::ATCRGB => &atc::atc_rgb,
+            CompressedFormat::ATCRGBAExplicit => &atc::atc_rgba_explicit,
+            CompressedFormat::ATCRGBAInterpolated => &atc::atc_rgba_interpolated,
+            CompressedFormat::ASTC => &|block, rgba| astc::astc(block, rgba, block_w, block_h),
+            CompressedFormat::PVRTC2BPP | CompressedFormat::PVRTC4BPP => {
+                let bpp2 = block_w == 8;
+                let rgba = pvrtc::pvrtc(&block_data, w, h, (blocks_x, blocks_y), bpp2);
+
+                return Ok(Self::finish_blocks(app, w, h, rgba));
+            }
         };
+        let rgba = decode_blocks(
+            &block_data,
+            w,
+            h,
+            (block_w, block_h),
+            format.bytes_per_block(),
+            decode,
+        );
+
+        Ok(Self::finish_blocks(app, w, h, rgba))
+    }
+
+    pub fn gx(
+        app: &App,
+        mut file: File,
+        w: usize,
+        h: usize,
+        offset: usize,
+    ) -> Result<Handle, String> {
+        let format = app.gx_format.selected;
+        let (block_w, block_h) = format.block_size();
+        let block_count = w.div_ceil(block_w) * h.div_ceil(block_h);
+
+        let mut block_data = vec![0; block_count * format.bytes_per_block()];
         file.seek(Start(offset as _))
             .map_err(|err| err.to_string())?;
-        file.read_exact(&mut pixel_datas)
-            .map_err(|err| format!("failed to fill pixel data buffer. {}", err.kind()))?;
+        file.read_exact(&mut block_data)
+            .map_err(|err| format!("failed to fill block data buffer. {}", err.kind()))?;
 
-        let mut tiles = Vec::with_capacity(tile_count);
-        let chunk_count = match palette.bpp {
-            Bpp::Bpp4 => tile_pixel_count / 2,
-            Bpp::Bpp8 => tile_pixel_count,
+        let palette = if format.is_paletted() {
+            Self::read_gx_palette(app, &mut file, format.color_count())?
+        } else {
+            Vec::new()
         };
-        for pixel_data in pixel_datas.chunks_exact(chunk_count) {
-            let mut tile_rgba = vec![0; tile_w * tile_h * 4];
-
-            match palette.bpp {
-                Bpp::Bpp4 => {
-                    for (i, &pixels) in pixel_data.into_iter().enumerate() {
-                        let src1 = (pixels & 0xF) as usize * 4;
-                        let src2 = ((pixels >> 4) & 0xF) as usize * 4;
-                        let dst1 = i * 2 * 4;
-                        let dst2 = dst1 + 4;
-
-                        tile_rgba[dst1..dst1 + 4].clone_from_slice(&palette_rgba[src1..src1 + 4]);
-                        tile_rgba[dst2..dst2 + 4].clone_from_slice(&palette_rgba[src2..src2 + 4]);
-                    }
-                }
-                Bpp::Bpp8 => {
-                    for (i, &pixel) in pixel_data.into_iter().enumerate() {
-                        let src = pixel as usize * 4;
-                        let dst = i * 4;
 
-                        tile_rgba[dst..dst + 4].clone_from_slice(&palette_rgba[src..src + 4]);
-                    }
-                }
-            }
+        let decode: &dyn Fn(&[u8], &mut [u8]) = match format {
+            GxFormat::I4 => &|block, rgba| gx::i4(block, rgba, app.bit_order),
+            GxFormat::I8 => &gx::i8,
+            GxFormat::IA4 => &gx::ia4,
+            GxFormat::IA8 => &|block, rgba| gx::color16(block, rgba, GxPaletteFormat::IA8),
+            GxFormat::RGB565 => &|block, rgba| gx::color16(block, rgba, GxPaletteFormat::RGB565),
+            GxFormat::RGB5A3 => &|block, rgba| gx::color16(block, rgba, GxPaletteFormat::RGB5A3),
+            GxFormat::RGBA32 => &gx::rgba32,
+            GxFormat::C4 => &|block, rgba| gx::c4(block, rgba, &palette, app.bit_order),
+            GxFormat::C8 => &|block, rgba| gx::c8(block, rgba, &palette),
+            GxFormat::C14X2 => &|block, rgba| gx::c14x2(block, rgba, &palette),
+            GxFormat::CMPR => &gx::cmpr,
+        };
+        let rgba = decode_blocks(
+            &block_data,
+            w,
+            h,
+            (block_w, block_h),
+            format.bytes_per_block(),
+            decode,
+        );
+
+        Ok(Self::finish_blocks(app, w, h, rgba))
+    }
+
+    /// Reads the palette of a `color_count` color GX format. Entries past the end
+    /// of the palette are black.
+    fn read_gx_palette(
+        app: &App,
+        file: &mut File,
+        color_count: usize,
+    ) -> Result<Vec<[u8; 4]>, String> {
+        let mut palette = match Self::read_palette_data(app, file, 16, color_count)? {
+            PaletteData::Parsed(rgba) => rgba
+                .chunks_exact(4)
+                .map(|color| color.try_into().unwrap())
+                .collect(),
+            PaletteData::Raw(data, _) => gx::palette(&data, app.gx_format.palette_format),
+        };
+        palette.resize(color_count, [0, 0, 0, 255]);
 
-            tiles.push(tile_rgba);
+        Ok(palette)
+    }
+
+    fn finish_blocks(app: &App, w: usize, h: usize, mut rgba: Vec<u8>) -> Handle {
+        if app.ignore_alpha {
+            rgba.chunks_exact_mut(4).for_each(|pixel| pixel[3] = 255);
         }
 
-        let mut rgba = vec![0; w * h * 4];
+        Self::new_handle(w as _, h as _, rgba)
+    }
+}
 
-        for y in 0..h {
-            for x in 0..w {
-                let tile_x = x / tile_w;
-                let tile_y = y / tile_h;
-                let tile = &tiles[tile_y * tile_row + tile_x];
+/// Palette colors as RGBA from a palette file format, or raw palette entries.
+enum PaletteData {
+    Parsed(Vec<u8>),
+    /// Raw bytes, and the number of entries asked for.
+    Raw(Vec<u8>, usize),
+}
 
-                let src = ((y % tile_h) * tile_w + (x % tile_w)) * 4;
-                let dst = (y * w + x) * 4;
+/// Reads `count` pixels of `bits` each at `offset`. If `positions` are given, the
+/// pixels are gathered from them instead of being read in order. Sub-byte pixels
+/// are packed in `bit_order`.
+fn read_pixels(
+    file: &mut File,
+    offset: usize,
+    count: usize,
+    bits: usize,
+    bit_order: BitOrder,
+    swap_words: bool,
+    positions: Option<Vec<usize>>,
+) -> Result<Vec<u8>, String> {
+    if positions.is_some() && !(bits.is_multiple_of(8) || 8 % bits == 0) {
+        return Err(format!("cannot swizzle pixels of {bits} bits"));
+    }
 
-                rgba[dst..dst + 4].copy_from_slice(&tile[src..src + 4]);
-            }
+    let stored = match &positions {
+        Some(positions) => positions.iter().max().map_or(0, |max| max + 1),
+        None => count,
+    };
+
+    let mut data = vec![0; (stored * bits).div_ceil(8)];
+    file.seek(Start(offset as _))
+        .map_err(|err| err.to_string())?;
+    file.read_exact(&mut data)
+        .map_err(|err| format!("failed to fill pixel data buffer. {}", err.kind()))?;
+
+    if swap_words {
+        for word in data.chunks_exact_mut(2) {
+            word.swap(0, 1);
         }
+    }
 
-        Ok(Self::new_handle(w as _, h as _, rgba))
+    match positions {
+        Some(positions) => Ok(unswizzle(&data, &positions, bits, bit_order)),
+        None => Ok(data),
+    }
+}
+
+/// Gathers the pixels of `bits` each found at `positions` of `data`.
+fn unswizzle(data: &[u8], positions: &[usize], bits: usize, bit_order: BitOrder) -> Vec<u8> {
+    let mut linear = vec![0; (positions.len() * bits).div_ceil(8)];
+
+    if bits >= 8 {
+        let size = bits / 8;
+        for (dst, &src) in linear.chunks_exact_mut(size).zip(positions) {
+            dst.copy_from_slice(&data[src * size..(src + 1) * size]);
+        }
+
+        return linear;
+    }
+
+    let mask = (1 << bits) - 1;
+    let shift = |i: usize| match bit_order {
+        BitOrder::LsbFirst => (i * bits) % 8,
+        BitOrder::MsbFirst => 8 - bits - (i * bits) % 8,
+    };
+    for (i, &src) in positions.iter().enumerate() {
+        let value = (data[src * bits / 8] >> shift(src)) & mask;
+        linear[i * bits / 8] |= value << shift(i);
+    }
+
+    linear
+}
+
+/// Unpacks `count` palette indices of `bpp` bits. Sub-byte indices are packed in
+/// `bit_order`, and 16-bit indices are in `endian`.
+fn unpack_indices(
+    data: &[u8],
+    bpp: Bpp,
+    bit_order: BitOrder,
+    endian: Endian,
+    count: usize,
+) -> Vec<usize> {
+    if bpp == Bpp::Bpp16 {
+        return data
+            .chunks_exact(2)
+            .take(count)
+            .map(|index| match endian {
+                Endian::LE => u16::from_le_bytes([index[0], index[1]]) as usize,
+                Endian::BE => u16::from_be_bytes([index[0], index[1]]) as usize,
+            })
+            .collect();
+    }
+
+    let bits = bpp.bits();
+    let mask = (1 << bits) - 1;
+
+    (0..count)
+        .map(|i| {
+            let bit = i * bits;
+            let shift = match bit_order {
+                BitOrder::LsbFirst => bit % 8,
+                BitOrder::MsbFirst => 8 - bits - bit % 8,
+            };
+
+            (data[bit / 8] as usize >> shift) & mask
+        })
+        .collect()
+}
+
+/// Looks up each index in the RGBA `palette`. Indices past the end of the palette
+/// are transparent black.
+fn fill_indexed(rgba: &mut [u8], indices: &[usize], palette: &[u8]) {
+    for (i, &index) in indices.iter().enumerate() {
+        let src = index * 4;
+        let dst = i * 4;
+
+        match palette.get(src..src + 4) {
+            Some(color) => rgba[dst..dst + 4].clone_from_slice(color),
+            None => rgba[dst..dst + 4].fill(0),
+        }
+    }
+}
+
+/// Assembles row-major `tiles` of `tile_w` x `tile_h` RGBA pixels into a `w` x `h`
+/// image.
+fn untile(tiles: &[Vec<u8>], w: usize, h: usize, (tile_w, tile_h): (usize, usize)) -> Vec<u8> {
+    let tile_row = w / tile_w;
+    let mut rgba = vec![0; w * h * 4];
+
+    for y in 0..h {
+        for x in 0..w {
+            let tile_x = x / tile_w;
+            let tile_y = y / tile_h;
+            let tile = &tiles[tile_y * tile_row + tile_x];
+
+            let src = ((y % tile_h) * tile_w + (x % tile_w)) * 4;
+            let dst = (y * w + x) * 4;
+
+            rgba[dst..dst + 4].copy_from_slice(&tile[src..src + 4]);
+        }
+    }
+
+    rgba
+}
+
+/// Decodes `data` block by block in row-major order. Blocks sticking out of the
+/// image because `w` or `h` is not a multiple of the block size are cropped.
+fn decode_blocks(
+    data: &[u8],
+    w: usize,
+    h: usize,
+    (block_w, block_h): (usize, usize),
+    bytes_per_block: usize,
+    decode: impl Fn(&[u8], &mut [u8]),
+) -> Vec<u8> {
+    let block_row = w.div_ceil(block_w);
+
+    let mut rgba = vec![0; w * h * 4];
+    let mut block_rgba = vec![0; block_w * block_h * 4];
+
+    for (i, block) in data.chunks_exact(bytes_per_block).enumerate() {
+        decode(block, &mut block_rgba);
+
+        let block_x = (i % block_row) * block_w;
+        let block_y = (i / block_row) * block_h;
+
+        for y in 0..block_h.min(h - block_y) {
+            let cols = block_w.min(w - block_x);
+            let src = y * block_w * 4;
+            let dst = ((block_y + y) * w + block_x) * 4;
+
+            rgba[dst..dst + cols * 4].copy_from_slice(&block_rgba[src..src + cols * 4]);
+        }
     }
+
+    rgba
+}
+
+/// Decodes a 3DS texture: 8x8 tiles in row-major order, each holding four blocks
+/// in Morton order, with the rows stored bottom-up.
+fn decode_3ds(
+    data: &[u8],
+    w: usize,
+    h: usize,
+    bytes_per_block: usize,
+    decode: fn(&[u8], &mut [u8]),
+) -> Vec<u8> {
+    let tiles: Vec<Vec<u8>> = data
+        .chunks_exact(bytes_per_block * 4)
+        .map(|tile| decode_blocks(tile, 8, 8, (4, 4), bytes_per_block, decode))
+        .collect();
+
+    let rgba = untile(&tiles, w, h, (8, 8));
+
+    rgba.chunks_exact(w * 4).rev().flatten().copied().collect()
 }
 
-fn fill_rgba(app: &App, rgba: &mut [u8], chunks: ChunksExact<u8>) -> Result<(), String> {
+/// Maps linear HDR colors to 8-bit with an exposure in stops, Reinhard tone
+/// mapping and a 2.2 gamma.
+fn tone_map(hdr: &[[f32; 3]], exposure: f32, rgba: &mut [u8]) {
+    let scale = exposure.exp2();
+
+    for (color, pixel) in hdr.iter().zip(rgba.chunks_exact_mut(4)) {
+        for (&c, p) in color.iter().zip(pixel.iter_mut()) {
+            let v = (c * scale).max(0.0);
+            let mapped = 1.0 - 1.0 / (1.0 + v);
+
+            *p = (mapped.powf(1.0 / 2.2) * 255.0).round() as u8;
+        }
+        pixel[3] = 255;
+    }
+}
+
+fn fill_rgba(
+    format: &PixelFormatState,
+    ignore_alpha: bool,
+    bit_order: BitOrder,
+    rgba: &mut [u8],
+    data: &[u8],
+) -> Result<(), String> {
     use super::pixel_format::{rgb_order, rgba_order};
 
-    let pixel_format = app.pixel_format.selected;
-    let Some(order) = pixel_format.valid_order(&app.pixel_format.component_order) else {
+    let pixel_format = format.selected;
+    let Some(order) = pixel_format.valid_order(&format.component_order) else {
         return Err("invalid component order".into());
     };
 
+    if pixel_format == PixelFormat::Custom {
+        return fill_custom(format, ignore_alpha, bit_order, rgba, data);
+    }
+    if pixel_format.is_integer() {
+        return fill_integer(format, ignore_alpha, rgba, data);
+    }
+
+    let chunks = data.chunks_exact(pixel_format.bytes_per_pixel());
+
     match pixel_format {
         PixelFormat::RGBA8888 => {
-            let (r_i, g_i, b_i, a_i) = rgba_order(&order)?;
+            let [r_src, g_src, b_src, a_src] = rgba_order(&order)?;
 
             for (i, chunk) in chunks.enumerate() {
-                let a = if app.ignore_alpha { 255 } else { chunk[a_i] };
+                let a = if ignore_alpha {
+                    255
+                } else {
+                    a_src.value(chunk)
+                };
 
-                rgba[i * 4] = chunk[r_i];
-                rgba[i * 4 + 1] = chunk[g_i];
-                rgba[i * 4 + 2] = chunk[b_i];
+                rgba[i * 4] = r_src.value(chunk);
+                rgba[i * 4 + 1] = g_src.value(chunk);
+                rgba[i * 4 + 2] = b_src.value(chunk);
                 rgba[i * 4 + 3] = a;
             }
         }
         PixelFormat::RGB888 => {
-            let (r_i, g_i, b_i) = rgb_order(&order)?;
+            let [r_src, g_src, b_src] = rgb_order(&order)?;
             let a = 255;
 
             for (i, chunk) in chunks.enumerate() {
-                rgba[i * 4] = chunk[r_i];
-                rgba[i * 4 + 1] = chunk[g_i];
-                rgba[i * 4 + 2] = chunk[b_i];
+                rgba[i * 4] = r_src.value(chunk);
+                rgba[i * 4 + 1] = g_src.value(chunk);
+                rgba[i * 4 + 2] = b_src.value(chunk);
                 rgba[i * 4 + 3] = a;
             }
         }
         PixelFormat::RGBA4444 => {
-            let (r_i, g_i, b_i, a_i) = rgba_order(&order)?;
+            let [r_src, g_src, b_src, a_src] = rgba_order(&order)?;
             let mut color = [0, 0, 0, 0];
 
             for (i, chunk) in chunks.enumerate() {
-                let pixel = match app.pixel_format.endian {
+                let pixel = match format.endian {
                     Endian::LE => u16::from_le_bytes([chunk[0], chunk[1]]),
                     Endian::BE => u16::from_be_bytes([chunk[0], chunk[1]]),
                 };
@@ -304,20 +726,24 @@ fn fill_rgba(app: &App, rgba: &mut [u8], chunks: ChunksExact<u8>) -> Result<(),
                 color[2] = ((pixel >> 8) & 0xF) as u8 * 17;
                 color[3] = ((pixel >> 12) & 0xF) as u8 * 17;
 
-                let a = if app.ignore_alpha { 255 } else { color[a_i] };
+                let a = if ignore_alpha {
+                    255
+                } else {
+                    a_src.value(&color)
+                };
 
-                rgba[i * 4] = color[r_i];
-                rgba[i * 4 + 1] = color[g_i];
-                rgba[i * 4 + 2] = color[b_i];
+                rgba[i * 4] = r_src.value(&color);
+                rgba[i * 4 + 1] = g_src.value(&color);
+                rgba[i * 4 + 2] = b_src.value(&color);
                 rgba[i * 4 + 3] = a;
             }
         }
         PixelFormat::RGBA5551 => {
-            let (r_i, g_i, b_i, a_i) = rgba_order(&order)?;
+            let [r_src, g_src, b_src, a_src] = rgba_order(&order)?;
             let mut color = [0, 0, 0, 0];
 
             for (i, chunk) in chunks.enumerate() {
-                let pixel = match app.pixel_format.endian {
+                let pixel = match format.endian {
                     Endian::LE => u16::from_le_bytes([chunk[0], chunk[1]]),
                     Endian::BE => u16::from_be_bytes([chunk[0], chunk[1]]),
                 };
@@ -331,21 +757,25 @@ fn fill_rgba(app: &App, rgba: &mut [u8], chunks: ChunksExact<u8>) -> Result<(),
                 color[1] += color[1] / 32;
                 color[2] += color[2] / 32;
 
-                let a = if app.ignore_alpha { 255 } else { color[a_i] };
+                let a = if ignore_alpha {
+                    255
+                } else {
+                    a_src.value(&color)
+                };
 
-                rgba[i * 4] = color[r_i];
-                rgba[i * 4 + 1] = color[g_i];
-                rgba[i * 4 + 2] = color[b_i];
+                rgba[i * 4] = r_src.value(&color);
+                rgba[i * 4 + 1] = g_src.value(&color);
+                rgba[i * 4 + 2] = b_src.value(&color);
                 rgba[i * 4 + 3] = a;
             }
         }
         PixelFormat::RGB565 => {
-            let (r_i, g_i, b_i) = rgb_order(&order)?;
+            let [r_src, g_src, b_src] = rgb_order(&order)?;
             let mut color = [0, 0, 0];
             let a = 255;
 
             for (i, chunk) in chunks.enumerate() {
-                let pixel = match app.pixel_format.endian {
+                let pixel = match format.endian {
                     Endian::LE => u16::from_le_bytes([chunk[0], chunk[1]]),
                     Endian::BE => u16::from_be_bytes([chunk[0], chunk[1]]),
                 };
@@ -358,9 +788,9 @@ fn fill_rgba(app: &App, rgba: &mut [u8], chunks: ChunksExact<u8>) -> Result<(),
                 color[1] += color[1] / 64;
                 color[2] += color[2] / 32;
 
-                rgba[i * 4] = color[r_i];
-                rgba[i * 4 + 1] = color[g_i];
-                rgba[i * 4 + 2] = color[b_i];
+                rgba[i * 4] = r_src.value(&color);
+                rgba[i * 4 + 1] = g_src.value(&color);
+                rgba[i * 4 + 2] = b_src.value(&color);
                 rgba[i * 4 + 3] = a;
             }
         }
@@ -390,7 +820,134 @@ fn fill_rgba(app: &App, rgba: &mut [u8], chunks: ChunksExact<u8>) -> Result<(),
                 rgba[i * 4 + 3] = 255;
             }
         }
+        PixelFormat::R16
+        | PixelFormat::RG16
+        | PixelFormat::RGBA16
+        | PixelFormat::R32
+        | PixelFormat::Custom => unreachable!(),
+    }
+
+    Ok(())
+}
+
+/// Decodes pixels of a custom bit layout, packed without padding. LE pixels are
+/// read from the low bits of each byte up and BE pixels from the high bits down,
+/// so whole-byte pixels are little or big-endian words. Pixels narrower than a
+/// byte follow the bit order instead.
+fn fill_custom(
+    format: &PixelFormatState,
+    ignore_alpha: bool,
+    bit_order: BitOrder,
+    rgba: &mut [u8],
+    data: &[u8],
+) -> Result<(), String> {
+    let (bits, channels) = format.custom.layout()?;
+    let endian = match bit_order {
+        _ if bits >= 8 => format.endian,
+        BitOrder::LsbFirst => Endian::LE,
+        BitOrder::MsbFirst => Endian::BE,
+    };
+
+    for (i, color) in rgba.chunks_exact_mut(4).enumerate() {
+        let pixel = read_bits(data, i * bits, bits, endian);
+
+        for (c, (dst, &(offset, width))) in color.iter_mut().zip(&channels).enumerate() {
+            *dst = match width {
+                0 if c == 3 => 255,
+                0 => 0,
+                _ => expand_bits((pixel >> offset) & (u64::MAX >> (64 - width)), width),
+            };
+        }
+
+        if ignore_alpha {
+            color[3] = 255;
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes 16 or 32-bit integer channels, with color channels mapped to 8 bits
+/// by the display mapping and alpha by its full range. Single channel formats
+/// are shown as gray, as they mostly hold depth or height.
+fn fill_integer(
+    format: &PixelFormatState,
+    ignore_alpha: bool,
+    rgba: &mut [u8],
+    data: &[u8],
+) -> Result<(), String> {
+    let integer = &format.integer;
+    let (channels, size) = format.selected.integer_channels();
+    let bits = size as u32 * 8;
+
+    let values: Vec<i64> = data
+        .chunks_exact(size)
+        .take(rgba.len() / 4 * channels)
+        .map(|bytes| integer.read(bytes, format.endian))
+        .collect();
+
+    let colors = values
+        .chunks_exact(channels)
+        .flat_map(|pixel| &pixel[..channels.min(3)])
+        .copied();
+    let window = integer.window(bits, colors)?;
+    let full_range = integer.full_range(bits);
+
+    let scale = |value: i64, (min, max): (i64, i64)| {
+        let value = value.clamp(min, max) - min;
+
+        (value as i128 * 255 / (max - min).max(1) as i128) as u8
+    };
+
+    for (pixel, color) in values.chunks_exact(channels).zip(rgba.chunks_exact_mut(4)) {
+        match *pixel {
+            [v] => color[..3].fill(scale(v, window)),
+            [r, g] => color[..3].copy_from_slice(&[scale(r, window), scale(g, window), 0]),
+            [r, g, b, a] => {
+                color[..3].copy_from_slice(&[scale(r, window), scale(g, window), scale(b, window)]);
+                color[3] = scale(a, full_range);
+            }
+            _ => unreachable!(),
+        }
+
+        if channels < 4 || ignore_alpha {
+            color[3] = 255;
+        }
     }
 
     Ok(())
 }
+
+/// Reads the `bits` wide value starting at bit `start` of `data`.
+fn read_bits(data: &[u8], start: usize, bits: usize, endian: Endian) -> u64 {
+    let bytes = &data[start / 8..(start + bits).div_ceil(8)];
+    let shift = start % 8;
+    let mask = u128::MAX >> (128 - bits);
+
+    let value = match endian {
+        Endian::LE => {
+            let word = bytes
+                .iter()
+                .rev()
+                .fold(0u128, |word, &byte| (word << 8) | byte as u128);
+
+            (word >> shift) & mask
+        }
+        Endian::BE => {
+            let word = bytes
+                .iter()
+                .fold(0u128, |word, &byte| (word << 8) | byte as u128);
+
+            (word >> (bytes.len() * 8 - shift - bits)) & mask
+        }
+    };
+
+    value as u64
+}
+
+/// Scales a `width` bit value to 8 bits, rounding to the nearest value.
+fn expand_bits(value: u64, width: usize) -> u8 {
+    let max = u128::MAX >> (128 - width);
+
+    ((value as u128 * 255 + max / 2) / max) as u8
+}
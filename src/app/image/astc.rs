@@ -0,0 +1,929 @@
+const ERROR_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+/// Trits, quints and bits of each integer sequence encoding range, from 2 to 256
+/// levels.
+#[rustfmt::skip]
+const RANGES: [(u32, u32, u32); 21] = [
+    (0, 0, 1), (1, 0, 0), (0, 0, 2), (0, 1, 0), (1, 0, 1), (0, 0, 3), (0, 1, 1),
+    (1, 0, 2), (0, 0, 4), (0, 1, 2), (1, 0, 3), (0, 0, 5), (0, 1, 3), (1, 0, 4),
+    (0, 0, 6), (0, 1, 4), (1, 0, 5), (0, 0, 7), (0, 1, 5), (1, 0, 6), (0, 0, 8),
+];
+
+struct BlockMode {
+    grid_w: usize,
+    grid_h: usize,
+    range: usize,
+    dual_plane: bool,
+}
+
+/// Decodes an LDR ASTC block of `block_w` x `block_h` pixels. Blocks that are
+/// invalid or use HDR endpoints decode as magenta.
+pub fn astc(block: &[u8], rgba: &mut [u8], block_w: usize, block_h: usize) {
+    let bits = u128::from_le_bytes(block[..16].try_into().unwrap());
+
+    if decode(bits, rgba, block_w, block_h).is_none() {
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&ERROR_COLOR);
+        }
+    }
+}
+
+fn decode(bits: u128, rgba: &mut [u8], block_w: usize, block_h: usize) -> Option<()> {
+    if bits & 0x1FF == 0x1FC {
+        return void_extent(bits, rgba);
+    }
+
+    let mode = block_mode(read(bits, 0, 11) as u32)?;
+    if mode.grid_w > block_w || mode.grid_h > block_h {
+        return None;
+    }
+
+    let partitions = read(bits, 11, 2) as usize + 1;
+    let plane_count = mode.dual_plane as usize + 1;
+    if partitions == 4 && mode.dual_plane {
+        return None;
+    }
+
+    let weight_count = mode.grid_w * mode.grid_h * plane_count;
+    let weight_bits = ise_bit_count(weight_count, mode.range);
+    if weight_count > 64 || !(24..=96).contains(&weight_bits) {
+        return None;
+    }
+
+    let mut below_weights = 128 - weight_bits;
+    let mut cems = [0; 4];
+    let mut seed = 0;
+    let color_start;
+
+    if partitions == 1 {
+        cems[0] = read(bits, 13, 4) as u32;
+        color_start = 17;
+    } else {
+        seed = read(bits, 13, 10) as u32;
+        color_start = 29;
+
+        let selector = read(bits, 23, 2) as u32;
+        if selector == 0 {
+            cems = [read(bits, 25, 4) as u32; 4];
+        } else {
+            let extra_bits = 3 * partitions as u32 - 4;
+            below_weights -= extra_bits;
+
+            let encoded =
+                read(bits, 25, 4) as u32 | ((read(bits, below_weights, extra_bits) as u32) << 4);
+            let base_class = selector - 1;
+
+            for (i, cem) in cems[..partitions].iter_mut().enumerate() {
+                let class = base_class + ((encoded >> i) & 1);
+                let m = (encoded >> (partitions + i * 2)) & 3;
+
+                *cem = (class << 2) | m;
+            }
+        }
+    }
+
+    let plane_component = if mode.dual_plane {
+        below_weights -= 2;
+        Some(read(bits, below_weights, 2) as usize)
+    } else {
+        None
+    };
+
+    let value_count: usize = cems[..partitions]
+        .iter()
+        .map(|cem| ((cem >> 2) as usize + 1) * 2)
+        .sum();
+    if value_count > 18 || below_weights < color_start {
+        return None;
+    }
+
+    let color_bits = below_weights - color_start;
+    let color_range = (0..RANGES.len())
+        .rev()
+        .find(|&range| ise_bit_count(value_count, range) <= color_bits)
+        .filter(|&range| range >= 4)?;
+
+    let values: Vec<u32> = ise_decode(bits, color_start, value_count, color_range)
+        .into_iter()
+        .map(|(tq, m)| unquantize_color(color_range, tq, m))
+        .collect();
+
+    let mut endpoints = [[[0u32; 4]; 2]; 4];
+    let mut offset = 0;
+    for (endpoint, &cem) in endpoints.iter_mut().zip(&cems[..partitions]) {
+        let count = ((cem >> 2) as usize + 1) * 2;
+        *endpoint = decode_endpoints(cem, &values[offset..offset + count])?;
+        offset += count;
+    }
+
+    let reversed = bits.reverse_bits();
+    let weights: Vec<u32> = ise_decode(reversed, 0, weight_count, mode.range)
+        .into_iter()
+        .map(|(tq, m)| unquantize_weight(mode.range, tq, m))
+        .collect();
+
+    let small_block = block_w * block_h < 31;
+    let ds = (1024 + block_w / 2) / (block_w - 1);
+    let dt = (1024 + block_h / 2) / (block_h - 1);
+
+    for y in 0..block_h {
+        for x in 0..block_w {
+            let partition = if partitions > 1 {
+                select_partition(seed, x, y, partitions, small_block)
+            } else {
+                0
+            };
+            let [e0, e1] = endpoints[partition];
+
+            let mut plane_weights = [0; 2];
+            for (plane, weight) in plane_weights.iter_mut().enumerate().take(plane_count) {
+                *weight = infill(&weights, &mode, plane, plane_count, ds * x, dt * y);
+            }
+
+            let dst = (y * block_w + x) * 4;
+            for channel in 0..4 {
+                let plane = (plane_component == Some(channel)) as usize;
+                let weight = plane_weights[plane];
+
+                let c0 = e0[channel] * 257;
+                let c1 = e1[channel] * 257;
+                let c = (c0 * (64 - weight) + c1 * weight + 32) >> 6;
+
+                rgba[dst + channel] = (c >> 8) as u8;
+            }
+        }
+    }
+
+    Some(())
+}
+
+/// Decodes a constant color block. Its extent must be all ones, or a non-empty
+/// rectangle.
+fn void_extent(bits: u128, rgba: &mut [u8]) -> Option<()> {
+    if read(bits, 9, 1) == 1 {
+        return None;
+    }
+
+    let [min_s, max_s, min_t, max_t] = [12, 25, 38, 51].map(|shift| read(bits, shift, 13));
+    let all_ones = [min_s, max_s, min_t, max_t].iter().all(|&c| c == 0x1FFF);
+    if !all_ones && (min_s >= max_s || min_t >= max_t) {
+        return None;
+    }
+
+    let color = [64, 80, 96, 112].map(|shift| (read(bits, shift, 16) >> 8) as u8);
+    for pixel in rgba.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&color);
+    }
+
+    Some(())
+}
+
+fn block_mode(mode: u32) -> Option<BlockMode> {
+    let mut range = (mode >> 4) & 1;
+    let mut high_precision = (mode >> 9) & 1 == 1;
+    let mut dual_plane = (mode >> 10) & 1 == 1;
+    let a = ((mode >> 5) & 3) as usize;
+
+    let (grid_w, grid_h) = if mode & 3 != 0 {
+        range |= (mode & 3) << 1;
+        let b = ((mode >> 7) & 3) as usize;
+
+        match (mode >> 2) & 3 {
+            0 => (b + 4, a + 2),
+            1 => (b + 8, a + 2),
+            2 => (a + 2, b + 8),
+            _ if mode & 0x100 != 0 => ((b & 1) + 2, a + 2),
+            _ => (a + 2, (b & 1) + 6),
+        }
+    } else {
+        range |= ((mode >> 2) & 3) << 1;
+        if (mode >> 2) & 3 == 0 {
+            return None;
+        }
+
+        let b = ((mode >> 9) & 3) as usize;
+        match (mode >> 7) & 3 {
+            0 => (12, a + 2),
+            1 => (a + 2, 12),
+            2 => {
+                high_precision = false;
+                dual_plane = false;
+                (a + 6, b + 6)
+            }
+            _ => match a {
+                0 => (6, 10),
+                1 => (10, 6),
+                _ => return None,
+            },
+        }
+    };
+
+    Some(BlockMode {
+        grid_w,
+        grid_h,
+        range: (range - 2) as usize + 6 * high_precision as usize,
+        dual_plane,
+    })
+}
+
+/// Reads `count` bits starting at `start`, least significant bit first.
+fn read(bits: u128, start: u32, count: u32) -> u128 {
+    (bits >> start) & ((1 << count) - 1)
+}
+
+fn ise_bit_count(count: usize, range: usize) -> u32 {
+    let (trits, quints, bits) = RANGES[range];
+    let count = count as u32;
+
+    count * bits + trits * (8 * count).div_ceil(5) + quints * (7 * count).div_ceil(3)
+}
+
+/// Decodes `count` integers as `(trit or quint, bits)` pairs. Bits past the end
+/// of the sequence read as zero.
+fn ise_decode(bits: u128, start: u32, count: usize, range: usize) -> Vec<(u32, u32)> {
+    let (trits, quints, bit_count) = RANGES[range];
+    let end = start + ise_bit_count(count, range);
+    let mut pos = start;
+    let mut next = |n: u32| {
+        let value = if pos < end {
+            read(bits, pos, n.min(end - pos)) as u32
+        } else {
+            0
+        };
+        pos += n;
+        value
+    };
+
+    let mut values = Vec::with_capacity(count + 4);
+
+    if trits == 1 {
+        while values.len() < count {
+            let mut m = [0; 5];
+            let mut t = 0;
+            for (i, shift) in [(0, 2), (2, 2), (4, 1), (5, 2), (7, 1)]
+                .into_iter()
+                .enumerate()
+            {
+                m[i] = next(bit_count);
+                t |= next(shift.1) << shift.0;
+            }
+            let decoded = decode_trits(t);
+            values.extend((0..5).map(|i| (decoded[i], m[i])));
+        }
+    } else if quints == 1 {
+        while values.len() < count {
+            let mut m = [0; 3];
+            let mut q = 0;
+            for (i, shift) in [(0, 3), (3, 2), (5, 2)].into_iter().enumerate() {
+                m[i] = next(bit_count);
+                q |= next(shift.1) << shift.0;
+            }
+            let decoded = decode_quints(q);
+            values.extend((0..3).map(|i| (decoded[i], m[i])));
+        }
+    } else {
+        values.extend((0..count).map(|_| (0, next(bit_count))));
+    }
+
+    values.truncate(count);
+    values
+}
+
+fn decode_trits(t: u32) -> [u32; 5] {
+    let bit = |value: u32, i: u32| (value >> i) & 1;
+    let (c, t3, t4);
+
+    if (t >> 2) & 7 == 7 {
+        c = (((t >> 5) & 7) << 2) | (t & 3);
+        t4 = 2;
+        t3 = 2;
+    } else {
+        c = t & 0x1F;
+        if (t >> 5) & 3 == 3 {
+            t4 = 2;
+            t3 = bit(t, 7);
+        } else {
+            t4 = bit(t, 7);
+            t3 = (t >> 5) & 3;
+        }
+    }
+
+    let (t0, t1, t2);
+    if c & 3 == 3 {
+        t2 = 2;
+        t1 = bit(c, 4);
+        t0 = (bit(c, 3) << 1) | (bit(c, 2) & !bit(c, 3) & 1);
+    } else if (c >> 2) & 3 == 3 {
+        t2 = 2;
+        t1 = 2;
+        t0 = c & 3;
+    } else {
+        t2 = bit(c, 4);
+        t1 = (c >> 2) & 3;
+        t0 = (bit(c, 1) << 1) | (bit(c, 0) & !bit(c, 1) & 1);
+    }
+
+    [t0, t1, t2, t3, t4]
+}
+
+fn decode_quints(q: u32) -> [u32; 3] {
+    let bit = |value: u32, i: u32| (value >> i) & 1;
+
+    if (q >> 1) & 3 == 3 && (q >> 5) & 3 == 0 {
+        let q2 =
+            (bit(q, 0) << 2) | ((bit(q, 4) & !bit(q, 0) & 1) << 1) | (bit(q, 3) & !bit(q, 0) & 1);
+
+        return [4, 4, q2];
+    }
+
+    let (q2, c) = if (q >> 1) & 3 == 3 {
+        (
+            4,
+            (((q >> 3) & 3) << 3) | ((!(q >> 5) & 3) << 1) | bit(q, 0),
+        )
+    } else {
+        ((q >> 5) & 3, q & 0x1F)
+    };
+
+    if c & 7 == 5 {
+        [(c >> 3) & 3, 4, q2]
+    } else {
+        [c & 7, (c >> 3) & 3, q2]
+    }
+}
+
+/// Repeats the `from` low bits of `value` until `to` bits are filled.
+fn replicate(value: u32, from: u32, to: u32) -> u32 {
+    let mut result = 0;
+    let mut filled = 0;
+
+    while filled < to {
+        result = (result << from) | value;
+        filled += from;
+    }
+
+    result >> (filled - to)
+}
+
+fn unquantize_color(range: usize, tq: u32, m: u32) -> u32 {
+    let (trits, quints, bits) = RANGES[range];
+    if trits + quints == 0 {
+        return replicate(m, bits, 8);
+    }
+
+    let bit = |i: u32| (m >> i) & 1;
+    let (b, c) = match (trits == 1, bits) {
+        (true, 1) => (0, 204),
+        (false, 1) => (0, 113),
+        (true, 2) => (bit(1) * 0x116, 93),
+        (false, 2) => (bit(1) * 0x10C, 54),
+        (true, 3) => (bit(2) * 0x10A + bit(1) * 0x85, 44),
+        (false, 3) => (bit(2) * 0x105 + bit(1) * 0x82, 26),
+        (true, 4) => (bit(3) * 0x104 + bit(2) * 0x82 + bit(1) * 0x41, 22),
+        (false, 4) => (bit(3) * 0x102 + bit(2) * 0x81 + bit(1) * 0x40, 13),
+        (true, 5) => (
+            bit(4) * 0x102 + bit(3) * 0x81 + bit(2) * 0x40 + bit(1) * 0x20,
+            11,
+        ),
+        (false, 5) => (
+            bit(4) * 0x101 + bit(3) * 0x80 + bit(2) * 0x40 + bit(1) * 0x20,
+            6,
+        ),
+        _ => (
+            bit(5) * 0x101 + bit(4) * 0x80 + bit(3) * 0x40 + bit(2) * 0x20 + bit(1) * 0x10,
+            5,
+        ),
+    };
+
+    let a = if m & 1 == 1 { 0x1FF } else { 0 };
+    let t = (tq * c + b) ^ a;
+
+    (a & 0x80) | (t >> 2)
+}
+
+fn unquantize_weight(range: usize, tq: u32, m: u32) -> u32 {
+    let (trits, quints, bits) = RANGES[range];
+
+    let value = if trits + quints == 0 {
+        replicate(m, bits, 6)
+    } else if bits == 0 {
+        match trits {
+            1 => [0, 32, 63][tq as usize],
+            _ => [0, 16, 32, 47, 63][tq as usize],
+        }
+    } else {
+        let bit = |i: u32| (m >> i) & 1;
+        let (b, c) = match (trits == 1, bits) {
+            (true, 1) => (0, 50),
+            (false, 1) => (0, 28),
+            (true, 2) => (bit(1) * 0x45, 23),
+            (false, 2) => (bit(1) * 0x42, 13),
+            _ => (bit(2) * 0x42 + bit(1) * 0x21, 11),
+        };
+
+        let a = if m & 1 == 1 { 0x7F } else { 0 };
+        let t = (tq * c + b) ^ a;
+
+        (a & 0x20) | (t >> 2)
+    };
+
+    if value > 32 { value + 1 } else { value }
+}
+
+fn decode_endpoints(cem: u32, v: &[u32]) -> Option<[[u32; 4]; 2]> {
+    let v: Vec<i32> = v.iter().map(|&v| v as i32).collect();
+    let clamp = |c: [i32; 4]| c.map(|c| c.clamp(0, 255) as u32);
+
+    let (e0, e1) = match cem {
+        0 => ([v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]),
+        1 => {
+            let l0 = (v[0] >> 2) | (v[1] & 0xC0);
+            let l1 = (l0 + (v[1] & 0x3F)).min(255);
+
+            ([l0, l0, l0, 255], [l1, l1, l1, 255])
+        }
+        4 => ([v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]),
+        5 => {
+            let (l1, l0) = bit_transfer_signed(v[1], v[0]);
+            let (a1, a0) = bit_transfer_signed(v[3], v[2]);
+
+            ([l0, l0, l0, a0], [l0 + l1, l0 + l1, l0 + l1, a0 + a1])
+        }
+        6 => (
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                255,
+            ],
+            [v[0], v[1], v[2], 255],
+        ),
+        8 | 12 => {
+            let (a0, a1) = if cem == 12 { (v[6], v[7]) } else { (255, 255) };
+
+            if v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+                ([v[0], v[2], v[4], a0], [v[1], v[3], v[5], a1])
+            } else {
+                (
+                    blue_contract([v[1], v[3], v[5], a1]),
+                    blue_contract([v[0], v[2], v[4], a0]),
+                )
+            }
+        }
+        9 | 13 => {
+            let (r1, r0) = bit_transfer_signed(v[1], v[0]);
+            let (g1, g0) = bit_transfer_signed(v[3], v[2]);
+            let (b1, b0) = bit_transfer_signed(v[5], v[4]);
+            let (a1, a0) = if cem == 13 {
+                bit_transfer_signed(v[7], v[6])
+            } else {
+                (0, 255)
+            };
+
+            if r1 + g1 + b1 >= 0 {
+                ([r0, g0, b0, a0], [r0 + r1, g0 + g1, b0 + b1, a0 + a1])
+            } else {
+                (
+                    blue_contract([r0 + r1, g0 + g1, b0 + b1, a0 + a1]),
+                    blue_contract([r0, g0, b0, a0]),
+                )
+            }
+        }
+        10 => (
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                v[4],
+            ],
+            [v[0], v[1], v[2], v[5]],
+        ),
+        _ => return None,
+    };
+
+    Some([clamp(e0), clamp(e1)])
+}
+
+/// Moves the top bit of `a` into `b` and sign-extends the remaining six bits.
+fn bit_transfer_signed(a: i32, b: i32) -> (i32, i32) {
+    let b = (b >> 1) | (a & 0x80);
+    let a = (a >> 1) & 0x3F;
+
+    if a & 0x20 != 0 { (a - 0x40, b) } else { (a, b) }
+}
+
+fn blue_contract(c: [i32; 4]) -> [i32; 4] {
+    [(c[0] + c[2]) >> 1, (c[1] + c[2]) >> 1, c[2], c[3]]
+}
+
+/// Bilinearly samples the weight grid for the pixel at fixed-point `(cs, ct)`.
+fn infill(
+    weights: &[u32],
+    mode: &BlockMode,
+    plane: usize,
+    plane_count: usize,
+    cs: usize,
+    ct: usize,
+) -> u32 {
+    let gs = (cs * (mode.grid_w - 1) + 32) >> 6;
+    let gt = (ct * (mode.grid_h - 1) + 32) >> 6;
+    let (js, fs) = (gs >> 4, (gs & 0xF) as u32);
+    let (jt, ft) = (gt >> 4, (gt & 0xF) as u32);
+
+    let w11 = (fs * ft + 8) >> 4;
+    let w10 = ft - w11;
+    let w01 = fs - w11;
+    let w00 = 16 + w11 - fs - ft;
+
+    let weight = |x: usize, y: usize| {
+        let x = x.min(mode.grid_w - 1);
+        let y = y.min(mode.grid_h - 1);
+
+        weights[(y * mode.grid_w + x) * plane_count + plane]
+    };
+
+    (weight(js, jt) * w00
+        + weight(js + 1, jt) * w01
+        + weight(js, jt + 1) * w10
+        + weight(js + 1, jt + 1) * w11
+        + 8)
+        >> 4
+}
+
+fn select_partition(seed: u32, x: usize, y: usize, partitions: usize, small_block: bool) -> usize {
+    let (x, y) = if small_block {
+        (x as u32 * 2, y as u32 * 2)
+    } else {
+        (x as u32, y as u32)
+    };
+
+    let seed = seed + (partitions as u32 - 1) * 1024;
+    let rnum = hash52(seed);
+
+    let mut seeds = [
+        rnum,
+        rnum >> 4,
+        rnum >> 8,
+        rnum >> 12,
+        rnum >> 16,
+        rnum >> 20,
+        rnum >> 24,
+        rnum >> 28,
+        rnum >> 18,
+        rnum >> 22,
+        rnum >> 26,
+        rnum.rotate_left(2),
+    ]
+    .map(|s| (s & 0xF) * (s & 0xF));
+
+    let (sh1, sh2) = if seed & 1 == 1 {
+        (
+            if seed & 2 != 0 { 4 } else { 5 },
+            if partitions == 3 { 6 } else { 5 },
+        )
+    } else {
+        (
+            if partitions == 3 { 6 } else { 5 },
+            if seed & 2 != 0 { 4 } else { 5 },
+        )
+    };
+    let sh3 = if seed & 0x10 != 0 { sh1 } else { sh2 };
+
+    for (i, s) in seeds.iter_mut().enumerate() {
+        *s >>= match i {
+            0..8 if i % 2 == 0 => sh1,
+            0..8 => sh2,
+            _ => sh3,
+        };
+    }
+
+    let a = (seeds[0] * x + seeds[1] * y + (rnum >> 14)) & 0x3F;
+    let b = (seeds[2] * x + seeds[3] * y + (rnum >> 10)) & 0x3F;
+    let c = match partitions {
+        3.. => (seeds[4] * x + seeds[5] * y + (rnum >> 6)) & 0x3F,
+        _ => 0,
+    };
+    let d = match partitions {
+        4.. => (seeds[6] * x + seeds[7] * y + (rnum >> 2)) & 0x3F,
+        _ => 0,
+    };
+
+    if a >= b && a >= c && a >= d {
+        0
+    } else if b >= c && b >= d {
+        1
+    } else if c >= d {
+        2
+    } else {
+        3
+    }
+}
+
+fn hash52(mut value: u32) -> u32 {
+    value ^= value >> 15;
+    value = value.wrapping_sub(value << 17);
+    value = value.wrapping_add(value << 7);
+    value = value.wrapping_add(value << 4);
+    value ^= value >> 5;
+    value = value.wrapping_add(value << 16);
+    value ^= value >> 7;
+    value ^= value >> 3;
+    value ^= value << 6;
+    value ^= value >> 17;
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks a block against the pixels decoded by Mesa's llvmpipe.
+    fn check(block: [u8; 16], block_w: usize, block_h: usize, expected: &[u8]) {
+        let mut rgba = vec![0; block_w * block_h * 4];
+        astc(&block, &mut rgba, block_w, block_h);
+
+        assert_eq!(rgba, expected);
+    }
+
+    /// A constant color block.
+    #[rustfmt::skip]
+    #[test]
+    fn void_extent() {
+        check(
+            [0xFC, 0xFD, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x59, 0x30, 0x26, 0x39, 0x38, 0xB3, 0x70, 0xA1],
+            4,
+            4,
+            &[
+                 48,  57, 179, 161,  48,  57, 179, 161,  48,  57, 179, 161,  48,  57, 179, 161,
+                 48,  57, 179, 161,  48,  57, 179, 161,  48,  57, 179, 161,  48,  57, 179, 161,
+                 48,  57, 179, 161,  48,  57, 179, 161,  48,  57, 179, 161,  48,  57, 179, 161,
+                 48,  57, 179, 161,  48,  57, 179, 161,  48,  57, 179, 161,  48,  57, 179, 161,
+            ],
+        );
+    }
+
+    /// A constant color block whose extent is an empty rectangle.
+    #[rustfmt::skip]
+    #[test]
+    fn void_extent_with_empty_extent() {
+        check(
+            [0xFC, 0x91, 0x2F, 0x89, 0x11, 0xE8, 0x18, 0x18, 0xF8, 0xC9, 0x9D, 0x5D, 0x5D, 0x98, 0x31, 0x95],
+            4,
+            4,
+            &[
+                255,   0, 255, 255, 255,   0, 255, 255, 255,   0, 255, 255, 255,   0, 255, 255,
+                255,   0, 255, 255, 255,   0, 255, 255, 255,   0, 255, 255, 255,   0, 255, 255,
+                255,   0, 255, 255, 255,   0, 255, 255, 255,   0, 255, 255, 255,   0, 255, 255,
+                255,   0, 255, 255, 255,   0, 255, 255, 255,   0, 255, 255, 255,   0, 255, 255,
+            ],
+        );
+    }
+
+    /// Endpoint mode 0, direct luminance.
+    #[rustfmt::skip]
+    #[test]
+    fn luminance_endpoints() {
+        check(
+            [0x22, 0x00, 0xBA, 0xF7, 0xEE, 0x37, 0xDF, 0x79, 0xA2, 0xD6, 0x98, 0xED, 0x2D, 0x64, 0x71, 0x99],
+            4,
+            4,
+            &[
+                189, 189, 189, 255, 155, 155, 155, 255, 189, 189, 189, 255, 155, 155, 155, 255,
+                166, 166, 166, 255, 134, 134, 134, 255, 211, 211, 211, 255, 155, 155, 155, 255,
+                155, 155, 155, 255, 143, 143, 143, 255, 201, 201, 201, 255, 175, 175, 175, 255,
+                155, 155, 155, 255, 189, 189, 189, 255, 155, 155, 155, 255, 221, 221, 221, 255,
+            ],
+        );
+    }
+
+    /// Endpoint mode 4, direct luminance and alpha.
+    #[rustfmt::skip]
+    #[test]
+    fn luminance_alpha_endpoints() {
+        check(
+            [0x5F, 0x83, 0x06, 0x37, 0x50, 0x95, 0x00, 0xE1, 0xDF, 0x6A, 0x1A, 0x44, 0x8B, 0xEC, 0xF3, 0x1C],
+            4,
+            4,
+            &[
+                 49,  49,  49,  94,  48,  48,  48,  93,  48,  48,  48,  93,  46,  46,  46,  91,
+                 66,  66,  66, 109,  71,  71,  71, 114,  77,  77,  77, 120,  82,  82,  82, 124,
+                 66,  66,  66, 109,  77,  77,  77, 120,  92,  92,  92, 133, 105, 105, 105, 145,
+                 95,  95,  95, 136, 102, 102, 102, 142, 111, 111, 111, 150, 118, 118, 118, 156,
+            ],
+        );
+    }
+
+    /// Endpoint mode 6, RGB and a scale.
+    #[rustfmt::skip]
+    #[test]
+    fn rgb_scale_endpoints() {
+        check(
+            [0x9E, 0xC3, 0xC2, 0x38, 0x4F, 0x51, 0x10, 0x2C, 0xC3, 0xC3, 0xE3, 0x09, 0xE4, 0x25, 0x14, 0xF1],
+            4,
+            4,
+            &[
+                 63, 102, 110, 255,  33,  53,  57, 255,  18,  30,  32, 255,  18,  30,  32, 255,
+                 74, 119, 127, 255,  51,  82,  88, 255,  35,  57,  61, 255,  27,  44,  48, 255,
+                 87, 140, 149, 255,  74, 119, 127, 255,  60,  96, 103, 255,  39,  63,  68, 255,
+                 97, 156, 167, 255,  92, 148, 158, 255,  76, 123, 132, 255,  48,  77,  83, 255,
+            ],
+        );
+    }
+
+    /// Endpoint mode 8, direct RGB.
+    #[rustfmt::skip]
+    #[test]
+    fn rgb_endpoints() {
+        check(
+            [0xBF, 0x03, 0x1D, 0x83, 0xA3, 0x16, 0x0C, 0x33, 0xAD, 0xD1, 0x56, 0xB1, 0xB4, 0xA1, 0xF5, 0x46],
+            4,
+            4,
+            &[
+                145,  76, 135, 255, 174,  37, 146, 255, 178,  31, 147, 255, 160,  57, 141, 255,
+                155,  63, 139, 255, 174,  37, 146, 255, 180,  29, 148, 255, 173,  38, 146, 255,
+                164,  50, 142, 255, 175,  36, 146, 255, 176,  34, 147, 255, 172,  39, 145, 255,
+                176,  35, 147, 255, 172,  39, 145, 255, 167,  47, 143, 255, 158,  59, 140, 255,
+            ],
+        );
+    }
+
+    /// Endpoint mode 9, RGB base and offset.
+    #[rustfmt::skip]
+    #[test]
+    fn rgb_base_offset_endpoints() {
+        check(
+            [0xDE, 0x21, 0x63, 0x34, 0x23, 0xC2, 0x7A, 0xFC, 0x2E, 0xAF, 0xA6, 0x4F, 0x5B, 0xA5, 0xCE, 0xFE],
+            4,
+            4,
+            &[
+                 95,  12,  29, 255,  93,  15,  29, 255,  92,  17,  29, 255,  91,  19,  30, 255,
+                 97,  10,  29, 255,  97,  10,  29, 255,  95,  12,  29, 255,  91,  19,  30, 255,
+                 94,  14,  29, 255,  92,  17,  29, 255,  93,  16,  29, 255,  97,  10,  29, 255,
+                 94,  14,  29, 255,  92,  17,  29, 255,  92,  17,  29, 255,  94,  14,  29, 255,
+            ],
+        );
+    }
+
+    /// Endpoint mode 10, RGB, a scale and two alphas.
+    #[rustfmt::skip]
+    #[test]
+    fn rgb_scale_alpha_endpoints() {
+        check(
+            [0x52, 0x40, 0x59, 0x42, 0x04, 0xB7, 0x92, 0x31, 0x24, 0x1E, 0x49, 0xB1, 0x29, 0x64, 0xEA, 0x9A],
+            4,
+            4,
+            &[
+                 22,  16,  67, 157,  36,  27, 109,  68,  29,  22,  88, 112,  44,  33, 130,  24,
+                 22,  16,  67, 157,  44,  33, 130,  24,  22,  16,  67, 157,  36,  27, 109,  68,
+                 15,  11,  46, 201,  22,  16,  67, 157,  15,  11,  46, 201,  22,  16,  67, 157,
+                 22,  16,  67, 157,  36,  27, 109,  68,  29,  22,  88, 112,  22,  16,  67, 157,
+            ],
+        );
+    }
+
+    /// Endpoint mode 12, direct RGBA.
+    #[rustfmt::skip]
+    #[test]
+    fn rgba_endpoints() {
+        check(
+            [0xBE, 0x83, 0x83, 0x48, 0x64, 0x36, 0x6E, 0x12, 0xFB, 0x42, 0x60, 0xA9, 0xFC, 0x06, 0x28, 0x0E],
+            4,
+            4,
+            &[
+                 59,  45,  71, 106,  48,  36, 102,  72,  47,  35, 106,  67,  54,  41,  84,  92,
+                 63,  48,  60, 119,  58,  45,  73, 105,  53,  41,  87,  89,  44,  33, 113,  60,
+                 59,  45,  70, 108,  59,  45,  70, 108,  56,  42,  80,  96,  45,  34, 110,  63,
+                 47,  36, 104,  70,  52,  40,  91,  85,  55,  42,  82,  95,  57,  44,  75, 102,
+            ],
+        );
+    }
+
+    /// Endpoint mode 13, RGBA base and offset.
+    #[rustfmt::skip]
+    #[test]
+    fn rgba_base_offset_endpoints() {
+        check(
+            [0xCE, 0xA3, 0x0F, 0x88, 0xE3, 0x84, 0xAB, 0x83, 0x89, 0x95, 0x42, 0xFF, 0xD0, 0xAB, 0xCA, 0x24],
+            4,
+            4,
+            &[
+                122,  80, 210, 242, 122,  80, 211, 241, 122,  80, 211, 240, 122,  80, 211, 241,
+                123,  80, 210, 243, 123,  80, 210, 243, 123,  81, 209, 246, 125,  83, 206, 253,
+                125,  82, 207, 251, 122,  80, 211, 242, 123,  80, 210, 243, 126,  84, 205, 255,
+                126,  84, 205, 255, 123,  81, 209, 245, 122,  80, 211, 241, 122,  80, 210, 242,
+            ],
+        );
+    }
+
+    /// Two partitions in a block large enough to use the full partition hash.
+    #[rustfmt::skip]
+    #[test]
+    fn two_partitions() {
+        check(
+            [0x3D, 0xE8, 0x26, 0x10, 0x6A, 0x5F, 0x62, 0xF5, 0xDA, 0x75, 0x06, 0x3E, 0xD6, 0x1F, 0x94, 0x1E],
+            6,
+            6,
+            &[
+                223, 152,   0, 255, 186, 113,  77, 255, 143,  67, 168, 255, 133,  57, 188, 255,
+                155,  80, 142, 255, 174, 100, 103, 255, 223, 152,   0, 255, 186, 113,  77, 255,
+                143,  67, 168, 255, 133,  57, 188, 255, 155,  80, 142, 255, 174, 100, 103, 255,
+                124,  48, 207, 255, 161,  87, 129, 255, 205, 133,  38, 255, 214, 142,  19, 255,
+                192, 119,  64, 255, 174, 100, 103, 255, 223, 152,   0, 255, 223, 152,   0, 255,
+                223, 152,   0, 255, 205, 133,  38, 255, 161,  87, 129, 255, 124,  48, 207, 255,
+                174, 100, 103, 255, 174, 100, 103, 255, 174, 100, 103, 255, 164,  90, 123, 255,
+                143,  67, 168, 255, 124,  48, 207, 255, 124,  48, 207, 255, 161,  87, 129, 255,
+                205, 133,  38, 255, 205, 133,  38, 255, 161,  87, 129, 255, 124,  48, 207, 255,
+            ],
+        );
+    }
+
+    /// Three partitions with different endpoint modes in a small block.
+    #[rustfmt::skip]
+    #[test]
+    fn three_partitions_mixed_endpoints() {
+        check(
+            [0xDE, 0xB1, 0x35, 0x0B, 0x5C, 0xD6, 0x29, 0xD3, 0x1F, 0x89, 0xD5, 0xD5, 0xF2, 0xF5, 0x0A, 0xB7],
+            4,
+            4,
+            &[
+                136, 183, 187, 255, 171, 209, 222, 255, 182, 217, 233, 255, 170, 209, 221, 255,
+                136, 183, 187, 255, 124, 174, 175, 255, 124, 174, 175, 255, 136, 183, 187, 255,
+                170, 209, 221, 255, 182, 217, 233, 255, 171, 209, 222, 255, 136, 183, 187, 255,
+                170, 209, 221, 255, 182, 217, 233, 255, 182, 217, 233, 255, 170, 209, 221, 255,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn four_partitions() {
+        check(
+            [0x42, 0x7A, 0x6E, 0xC2, 0x60, 0xFF, 0xB3, 0x34, 0x99, 0xDF, 0x3A, 0x4A, 0xFF, 0x88, 0x18, 0x72],
+            4,
+            4,
+            &[
+                151, 151, 151, 255,  27,  27,  27, 255,  34,  34,  34, 255, 184, 184, 184, 255,
+                 21,  21,  21, 255, 100, 100, 100, 255,  47,  47,  47, 255, 228, 228, 228, 255,
+                 23,  23,  23, 255, 116, 116, 116, 255, 143, 143, 143, 255, 116, 116, 116, 255,
+                216, 216, 216, 255, 154, 154, 154, 255, 132, 132, 132, 255, 222, 222, 222, 255,
+            ],
+        );
+    }
+
+    /// Two weight planes.
+    #[rustfmt::skip]
+    #[test]
+    fn dual_plane() {
+        check(
+            [0x5F, 0x85, 0x51, 0xFD, 0x2E, 0xFD, 0xFF, 0xA0, 0xEB, 0x31, 0x69, 0x3A, 0x94, 0xB8, 0x9D, 0x66],
+            4,
+            4,
+            &[
+                107, 145, 237, 191, 107, 149, 231, 192, 107, 152, 225, 193, 107, 156, 219, 194,
+                100, 179, 182, 201,  95, 164, 205, 197,  87, 149, 231, 192,  82, 134, 254, 187,
+                113, 200, 147, 208, 115, 204, 141, 209, 117, 208, 135, 210, 119, 211, 129, 211,
+                107, 156, 219, 194, 107, 156, 219, 194, 107, 156, 219, 194, 107, 156, 219, 194,
+            ],
+        );
+    }
+
+    /// A 5x4 footprint with a smaller weight grid.
+    #[rustfmt::skip]
+    #[test]
+    fn non_square_footprint() {
+        check(
+            [0xBE, 0x03, 0x88, 0x1F, 0x09, 0xBD, 0x31, 0x82, 0x84, 0x6C, 0x8C, 0x54, 0xF5, 0xC1, 0x56, 0xEC],
+            5,
+            4,
+            &[
+                156, 156, 156, 255, 155, 155, 155, 255, 154, 154, 154, 255, 150, 150, 150, 255,
+                146, 146, 146, 255, 156, 156, 156, 255, 171, 171, 171, 255, 183, 183, 183, 255,
+                173, 173, 173, 255, 164, 164, 164, 255, 158, 158, 158, 255, 167, 167, 167, 255,
+                180, 180, 180, 255, 173, 173, 173, 255, 164, 164, 164, 255, 162, 162, 162, 255,
+                154, 154, 154, 255, 146, 146, 146, 255, 146, 146, 146, 255, 146, 146, 146, 255,
+            ],
+        );
+    }
+
+    /// A 6x6 footprint with a smaller weight grid.
+    #[rustfmt::skip]
+    #[test]
+    fn weight_grid_infill() {
+        check(
+            [0x52, 0x20, 0xFB, 0x9D, 0x20, 0x2F, 0x31, 0x48, 0xC9, 0x92, 0xB6, 0x39, 0x1A, 0x81, 0x96, 0xEC],
+            6,
+            6,
+            &[
+                113, 206, 149, 255, 106, 209, 155, 255, 101, 211, 158, 255, 101, 211, 158, 255,
+                106, 209, 155, 255, 113, 206, 149, 255, 113, 206, 149, 255, 110, 207, 151, 255,
+                110, 207, 151, 255, 115, 205, 148, 255, 112, 206, 150, 255, 106, 209, 155, 255,
+                116, 205, 147, 255, 113, 206, 150, 255, 114, 205, 149, 255, 123, 202, 142, 255,
+                117, 204, 146, 255, 103, 210, 156, 255, 124, 201, 142, 255, 111, 207, 151, 255,
+                107, 209, 154, 255, 122, 202, 143, 255, 121, 203, 144, 255, 111, 207, 151, 255,
+                126, 200, 140, 255, 110, 207, 151, 255, 105, 210, 155, 255, 117, 204, 146, 255,
+                117, 204, 146, 255, 111, 207, 151, 255, 126, 200, 140, 255, 110, 207, 151, 255,
+                103, 210, 156, 255, 111, 207, 151, 255, 111, 207, 151, 255, 107, 209, 154, 255,
+            ],
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use super::bc::{explicit_alpha, interpolated_alpha};
+
+/// Decodes an ATC RGB block into 16 RGBA pixels.
+pub fn atc_rgb(block: &[u8], rgba: &mut [u8]) {
+    color_block(&block[..8], rgba);
+}
+
+/// Decodes an ATC RGBA block with 4-bit explicit alpha stored before the color.
+pub fn atc_rgba_explicit(block: &[u8], rgba: &mut [u8]) {
+    color_block(&block[8..16], rgba);
+    explicit_alpha(&block[..8], rgba);
+}
+
+/// Decodes an ATC RGBA block with BC3-style interpolated alpha stored before the
+/// color.
+pub fn atc_rgba_interpolated(block: &[u8], rgba: &mut [u8]) {
+    color_block(&block[8..16], rgba);
+    interpolated_alpha(&block[..8], rgba);
+}
+
+/// Decodes the 8-byte ATC color block. The top bit of the RGB555 `color0` selects
+/// between interpolating both colors and a mode with black and `color0 - color1 / 4`.
+fn color_block(block: &[u8], rgba: &mut [u8]) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let rgb0 = [(c0 >> 10) & 0x1F, (c0 >> 5) & 0x1F, c0 & 0x1F].map(|c| extend5(c as u8));
+    let rgb1 = [
+        extend5(((c1 >> 11) & 0x1F) as u8),
+        extend6(((c1 >> 5) & 0x3F) as u8),
+        extend5((c1 & 0x1F) as u8),
+    ];
+    let mut colors = [[0u8; 4]; 4];
+
+    for i in 0..3 {
+        let (a, b) = (rgb0[i] as u16, rgb1[i] as u16);
+
+        if c0 & 0x8000 != 0 {
+            colors[1][i] = a.saturating_sub(b / 4) as u8;
+            colors[2][i] = a as u8;
+        } else {
+            colors[0][i] = a as u8;
+            colors[1][i] = ((5 * a + 3 * b) / 8) as u8;
+            colors[2][i] = ((3 * a + 5 * b) / 8) as u8;
+        }
+        colors[3][i] = b as u8;
+    }
+    colors.iter_mut().for_each(|color| color[3] = 255);
+
+    for i in 0..16 {
+        let index = (indices >> (i * 2)) & 0x3;
+
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&colors[index as usize]);
+    }
+}
+
+fn extend5(value: u8) -> u8 {
+    (value << 3) | (value >> 2)
+}
+
+fn extend6(value: u8) -> u8 {
+    (value << 2) | (value >> 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks a block against pixels worked out by hand from the ATC rules.
+    fn check(decode: fn(&[u8], &mut [u8]), block: &[u8], expected: [u8; 64]) {
+        let mut rgba = [0; 64];
+        decode(block, &mut rgba);
+
+        assert_eq!(rgba, expected);
+    }
+
+    /// `color0` is (255, 132, 0) and `color1` is (0, 255, 255), with the indices
+    /// counting up along each row.
+    #[rustfmt::skip]
+    #[test]
+    fn rgb_interpolated_colors() {
+        check(
+            atc_rgb,
+            &[0x00, 0x7E, 0xFF, 0x07, 0xE4, 0xE4, 0xE4, 0xE4],
+            [
+                255, 132,   0, 255, 159, 178,  95, 255,  95, 208, 159, 255,   0, 255, 255, 255,
+                255, 132,   0, 255, 159, 178,  95, 255,  95, 208, 159, 255,   0, 255, 255, 255,
+                255, 132,   0, 255, 159, 178,  95, 255,  95, 208, 159, 255,   0, 255, 255, 255,
+                255, 132,   0, 255, 159, 178,  95, 255,  95, 208, 159, 255,   0, 255, 255, 255,
+            ],
+        );
+    }
+
+    /// The top bit of `color0` selects black, `color0 - color1 / 4`, `color0` and
+    /// `color1`.
+    #[rustfmt::skip]
+    #[test]
+    fn rgb_alternate_colors() {
+        check(
+            atc_rgb,
+            &[0x00, 0xFE, 0x04, 0x44, 0xE4, 0xE4, 0xE4, 0xE4],
+            [
+                  0,   0,   0, 255, 239, 100,   0, 255, 255, 132,   0, 255,  66, 130,  33, 255,
+                  0,   0,   0, 255, 239, 100,   0, 255, 255, 132,   0, 255,  66, 130,  33, 255,
+                  0,   0,   0, 255, 239, 100,   0, 255, 255, 132,   0, 255,  66, 130,  33, 255,
+                  0,   0,   0, 255, 239, 100,   0, 255, 255, 132,   0, 255,  66, 130,  33, 255,
+            ],
+        );
+    }
+
+    /// Alpha counts up in steps of 17.
+    #[rustfmt::skip]
+    #[test]
+    fn explicit_alpha() {
+        check(
+            atc_rgba_explicit,
+            &[0x10, 0x32, 0x54, 0x76, 0x98, 0xBA, 0xDC, 0xFE, 0x00, 0x7E, 0xFF, 0x07, 0xE4, 0xE4, 0xE4, 0xE4],
+            [
+                255, 132,   0,   0, 159, 178,  95,  17,  95, 208, 159,  34,   0, 255, 255,  51,
+                255, 132,   0,  68, 159, 178,  95,  85,  95, 208, 159, 102,   0, 255, 255, 119,
+                255, 132,   0, 136, 159, 178,  95, 153,  95, 208, 159, 170,   0, 255, 255, 187,
+                255, 132,   0, 204, 159, 178,  95, 221,  95, 208, 159, 238,   0, 255, 255, 255,
+            ],
+        );
+    }
+
+    /// `alpha0 > alpha1` interpolates six values between them.
+    #[rustfmt::skip]
+    #[test]
+    fn interpolated_alpha() {
+        check(
+            atc_rgba_interpolated,
+            &[0xFF, 0x00, 0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA, 0x00, 0x7E, 0xFF, 0x07, 0xE4, 0xE4, 0xE4, 0xE4],
+            [
+                255, 132,   0, 255, 159, 178,  95,   0,  95, 208, 159, 218,   0, 255, 255, 182,
+                255, 132,   0, 145, 159, 178,  95, 109,  95, 208, 159,  72,   0, 255, 255,  36,
+                255, 132,   0, 255, 159, 178,  95,   0,  95, 208, 159, 218,   0, 255, 255, 182,
+                255, 132,   0, 145, 159, 178,  95, 109,  95, 208, 159,  72,   0, 255, 255,  36,
+            ],
+        );
+    }
+
+    /// `alpha0 <= alpha1` interpolates four values and adds 0 and 255.
+    #[rustfmt::skip]
+    #[test]
+    fn interpolated_alpha_with_extremes() {
+        check(
+            atc_rgba_interpolated,
+            &[0x28, 0xF0, 0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA, 0x00, 0x7E, 0xFF, 0x07, 0xE4, 0xE4, 0xE4, 0xE4],
+            [
+                255, 132,   0,  40, 159, 178,  95, 240,  95, 208, 159,  80,   0, 255, 255, 120,
+                255, 132,   0, 160, 159, 178,  95, 200,  95, 208, 159,   0,   0, 255, 255, 255,
+                255, 132,   0,  40, 159, 178,  95, 240,  95, 208, 159,  80,   0, 255, 255, 120,
+                255, 132,   0, 160, 159, 178,  95, 200,  95, 208, 159,   0,   0, 255, 255, 255,
+            ],
+        );
+    }
+}
@@ -0,0 +1,187 @@
+/// Decodes a BC1 (DXT1) block into 16 RGBA pixels.
+pub fn bc1(block: &[u8], rgba: &mut [u8]) {
+    color_block(&block[..8], rgba, true);
+}
+
+/// Decodes a BC2 (DXT3) block, which stores 4-bit explicit alpha before the color.
+pub fn bc2(block: &[u8], rgba: &mut [u8]) {
+    color_block(&block[8..16], rgba, false);
+    explicit_alpha(&block[..8], rgba);
+}
+
+/// Decodes a BC3 (DXT5) block, which stores interpolated alpha before the color.
+pub fn bc3(block: &[u8], rgba: &mut [u8]) {
+    color_block(&block[8..16], rgba, false);
+    interpolated_alpha(&block[..8], rgba);
+}
+
+/// Fills the alpha of 16 RGBA pixels from an 8-byte block of 4-bit values.
+pub fn explicit_alpha(block: &[u8], rgba: &mut [u8]) {
+    let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+
+    for i in 0..16 {
+        rgba[i * 4 + 3] = ((alpha >> (i * 4)) & 0xF) as u8 * 17;
+    }
+}
+
+/// Fills the alpha of 16 RGBA pixels from an 8-byte BC3 alpha block.
+pub fn interpolated_alpha(block: &[u8], rgba: &mut [u8]) {
+    let mut alpha = [0; 16];
+    alpha_block(block, &mut alpha);
+
+    for (i, a) in alpha.into_iter().enumerate() {
+        rgba[i * 4 + 3] = a;
+    }
+}
+
+/// Decodes a BC4 (ATI1) block as grayscale.
+pub fn bc4(block: &[u8], rgba: &mut [u8], signed: bool) {
+    let values = channel_block(&block[..8], signed);
+
+    for (i, v) in values.into_iter().enumerate() {
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&[v, v, v, 255]);
+    }
+}
+
+/// Decodes a BC5 (ATI2) block into red and green. With `reconstruct_z`, blue is
+/// rebuilt from both channels as the Z component of a unit normal.
+pub fn bc5(block: &[u8], rgba: &mut [u8], signed: bool, reconstruct_z: bool) {
+    let xs = channel_block(&block[..8], signed);
+    let ys = channel_block(&block[8..16], signed);
+
+    for (i, (x, y)) in xs.into_iter().zip(ys).enumerate() {
+        let z = if reconstruct_z { normal_z(x, y) } else { 0 };
+
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&[x, y, z, 255]);
+    }
+}
+
+/// Rebuilds the Z component of a unit normal from its 8-bit encoded X and Y.
+pub fn normal_z(x: u8, y: u8) -> u8 {
+    let nx = x as f32 / 127.5 - 1.0;
+    let ny = y as f32 / 127.5 - 1.0;
+    let nz = (1.0 - nx * nx - ny * ny).max(0.0).sqrt();
+
+    ((nz + 1.0) * 127.5).round() as u8
+}
+
+/// Decodes an 8-byte BC4 channel. Signed values are remapped from -127..=127 to
+/// 0..=255 so that -1.0 shows as black.
+fn channel_block(block: &[u8], signed: bool) -> [u8; 16] {
+    let mut values = [0; 16];
+
+    if !signed {
+        alpha_block(block, &mut values);
+        return values;
+    }
+
+    let e0 = (block[0] as i8).max(-127) as i16;
+    let e1 = (block[1] as i8).max(-127) as i16;
+    let mut bits = [0; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bits);
+
+    let mut palette = [0i16; 8];
+    palette[0] = e0;
+    palette[1] = e1;
+
+    if e0 > e1 {
+        for i in 1..7 {
+            palette[i + 1] = ((7 - i) as i16 * e0 + i as i16 * e1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((5 - i) as i16 * e0 + i as i16 * e1) / 5;
+        }
+        palette[6] = -127;
+        palette[7] = 127;
+    }
+
+    for (i, value) in values.iter_mut().enumerate() {
+        let v = palette[((indices >> (i * 3)) & 0x7) as usize];
+
+        *value = ((v + 127) as u16 * 255 / 254) as u8;
+    }
+
+    values
+}
+
+/// Decodes the 8-byte unsigned interpolated channel of BC3, BC4 and BC5. `a0 > a1`
+/// selects six interpolated values, otherwise four plus 0 and 255.
+fn alpha_block(block: &[u8], values: &mut [u8; 16]) {
+    let a0 = block[0] as u16;
+    let a1 = block[1] as u16;
+    let mut bits = [0; 8];
+    bits[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bits);
+
+    let mut alphas = [0u8; 8];
+    alphas[0] = a0 as u8;
+    alphas[1] = a1 as u8;
+
+    if a0 > a1 {
+        for i in 1..7 {
+            alphas[i + 1] = (((7 - i) as u16 * a0 + i as u16 * a1) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            alphas[i + 1] = (((5 - i) as u16 * a0 + i as u16 * a1) / 5) as u8;
+        }
+        alphas[6] = 0;
+        alphas[7] = 255;
+    }
+
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = alphas[((indices >> (i * 3)) & 0x7) as usize];
+    }
+}
+
+/// Decodes the 8-byte color part shared by BC1, BC2 and BC3. When `punch_through`
+/// is set and `color0 <= color1`, the block uses three colors and transparent black.
+fn color_block(block: &[u8], rgba: &mut [u8], punch_through: bool) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let rgb0 = rgb565(c0);
+    let rgb1 = rgb565(c1);
+    let mut colors = [[0u8; 4]; 4];
+
+    colors[0] = [rgb0[0], rgb0[1], rgb0[2], 255];
+    colors[1] = [rgb1[0], rgb1[1], rgb1[2], 255];
+
+    if c0 > c1 || !punch_through {
+        for i in 0..3 {
+            let (a, b) = (rgb0[i] as u16, rgb1[i] as u16);
+
+            colors[2][i] = ((2 * a + b) / 3) as u8;
+            colors[3][i] = ((a + 2 * b) / 3) as u8;
+        }
+        colors[2][3] = 255;
+        colors[3][3] = 255;
+    } else {
+        for i in 0..3 {
+            colors[2][i] = ((rgb0[i] as u16 + rgb1[i] as u16) / 2) as u8;
+        }
+        colors[2][3] = 255;
+        colors[3] = [0, 0, 0, 0];
+    }
+
+    for i in 0..16 {
+        let index = (indices >> (i * 2)) & 0x3;
+
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&colors[index as usize]);
+    }
+}
+
+fn rgb565(color: u16) -> [u8; 3] {
+    let r = ((color >> 11) & 0x1F) as u8;
+    let g = ((color >> 5) & 0x3F) as u8;
+    let b = (color & 0x1F) as u8;
+
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
@@ -0,0 +1,382 @@
+use super::bc7::{ANCHORS_2, Bits, PARTITIONS_2, weight};
+
+/// Endpoint component as `(endpoint, channel)`, with endpoints ordered w, x, y, z.
+type Field = (usize, usize);
+
+const RW: Field = (0, 0);
+const GW: Field = (0, 1);
+const BW: Field = (0, 2);
+const RX: Field = (1, 0);
+const GX: Field = (1, 1);
+const BX: Field = (1, 2);
+const RY: Field = (2, 0);
+const GY: Field = (2, 1);
+const BY: Field = (2, 2);
+const RZ: Field = (3, 0);
+const GZ: Field = (3, 1);
+const BZ: Field = (3, 2);
+
+struct Mode {
+    two_regions: bool,
+    transformed: bool,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    /// Endpoint bits in stream order as `(field, shift, count)`.
+    layout: &'static [(Field, u32, u32)],
+}
+
+#[rustfmt::skip]
+const MODES: [Mode; 14] = [
+    Mode { two_regions: true, transformed: true, endpoint_bits: 10, delta_bits: [5, 5, 5], layout: &[
+        (GY, 4, 1), (BY, 4, 1), (BZ, 4, 1), (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 5),
+        (GZ, 4, 1), (GY, 0, 4), (GX, 0, 5), (BZ, 0, 1), (GZ, 0, 4), (BX, 0, 5), (BZ, 1, 1),
+        (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { two_regions: true, transformed: true, endpoint_bits: 7, delta_bits: [6, 6, 6], layout: &[
+        (GY, 5, 1), (GZ, 4, 1), (GZ, 5, 1), (RW, 0, 7), (BZ, 0, 1), (BZ, 1, 1), (BY, 4, 1),
+        (GW, 0, 7), (BY, 5, 1), (BZ, 2, 1), (GY, 4, 1), (BW, 0, 7), (BZ, 3, 1), (BZ, 5, 1),
+        (BZ, 4, 1), (RX, 0, 6), (GY, 0, 4), (GX, 0, 6), (GZ, 0, 4), (BX, 0, 6), (BY, 0, 4),
+        (RY, 0, 6), (RZ, 0, 6),
+    ] },
+    Mode { two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [5, 4, 4], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 5), (RW, 10, 1), (GY, 0, 4), (GX, 0, 4),
+        (GW, 10, 1), (BZ, 0, 1), (GZ, 0, 4), (BX, 0, 4), (BW, 10, 1), (BZ, 1, 1), (BY, 0, 4),
+        (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [4, 5, 4], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 4), (RW, 10, 1), (GZ, 4, 1), (GY, 0, 4),
+        (GX, 0, 5), (GW, 10, 1), (GZ, 0, 4), (BX, 0, 4), (BW, 10, 1), (BZ, 1, 1), (BY, 0, 4),
+        (RY, 0, 4), (BZ, 0, 1), (BZ, 2, 1), (RZ, 0, 4), (GY, 4, 1), (BZ, 3, 1),
+    ] },
+    Mode { two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [4, 4, 5], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 4), (RW, 10, 1), (BY, 4, 1), (GY, 0, 4),
+        (GX, 0, 4), (GW, 10, 1), (BZ, 0, 1), (GZ, 0, 4), (BX, 0, 5), (BW, 10, 1), (BY, 0, 4),
+        (RY, 0, 4), (BZ, 1, 1), (BZ, 2, 1), (RZ, 0, 4), (BZ, 4, 1), (BZ, 3, 1),
+    ] },
+    Mode { two_regions: true, transformed: true, endpoint_bits: 9, delta_bits: [5, 5, 5], layout: &[
+        (RW, 0, 9), (BY, 4, 1), (GW, 0, 9), (GY, 4, 1), (BW, 0, 9), (BZ, 4, 1), (RX, 0, 5),
+        (GZ, 4, 1), (GY, 0, 4), (GX, 0, 5), (BZ, 0, 1), (GZ, 0, 4), (BX, 0, 5), (BZ, 1, 1),
+        (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [6, 5, 5], layout: &[
+        (RW, 0, 8), (GZ, 4, 1), (BY, 4, 1), (GW, 0, 8), (BZ, 2, 1), (GY, 4, 1), (BW, 0, 8),
+        (BZ, 3, 1), (BZ, 4, 1), (RX, 0, 6), (GY, 0, 4), (GX, 0, 5), (BZ, 0, 1), (GZ, 0, 4),
+        (BX, 0, 5), (BZ, 1, 1), (BY, 0, 4), (RY, 0, 6), (RZ, 0, 6),
+    ] },
+    Mode { two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [5, 6, 5], layout: &[
+        (RW, 0, 8), (BZ, 0, 1), (BY, 4, 1), (GW, 0, 8), (GY, 5, 1), (GY, 4, 1), (BW, 0, 8),
+        (GZ, 5, 1), (BZ, 4, 1), (RX, 0, 5), (GZ, 4, 1), (GY, 0, 4), (GX, 0, 6), (GZ, 0, 4),
+        (BX, 0, 5), (BZ, 1, 1), (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [5, 5, 6], layout: &[
+        (RW, 0, 8), (BZ, 1, 1), (BY, 4, 1), (GW, 0, 8), (BY, 5, 1), (GY, 4, 1), (BW, 0, 8),
+        (BZ, 5, 1), (BZ, 4, 1), (RX, 0, 5), (GZ, 4, 1), (GY, 0, 4), (GX, 0, 5), (BZ, 0, 1),
+        (GZ, 0, 4), (BX, 0, 6), (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { two_regions: true, transformed: false, endpoint_bits: 6, delta_bits: [6, 6, 6], layout: &[
+        (RW, 0, 6), (GZ, 4, 1), (BZ, 0, 1), (BZ, 1, 1), (BY, 4, 1), (GW, 0, 6), (GY, 5, 1),
+        (BY, 5, 1), (BZ, 2, 1), (GY, 4, 1), (BW, 0, 6), (GZ, 5, 1), (BZ, 3, 1), (BZ, 5, 1),
+        (BZ, 4, 1), (RX, 0, 6), (GY, 0, 4), (GX, 0, 6), (GZ, 0, 4), (BX, 0, 6), (BY, 0, 4),
+        (RY, 0, 6), (RZ, 0, 6),
+    ] },
+    Mode { two_regions: false, transformed: false, endpoint_bits: 10, delta_bits: [10, 10, 10], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 10), (GX, 0, 10), (BX, 0, 10),
+    ] },
+    Mode { two_regions: false, transformed: true, endpoint_bits: 11, delta_bits: [9, 9, 9], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 9), (RW, 10, 1), (GX, 0, 9), (GW, 10, 1),
+        (BX, 0, 9), (BW, 10, 1),
+    ] },
+    Mode { two_regions: false, transformed: true, endpoint_bits: 12, delta_bits: [8, 8, 8], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 8), (RW, 11, 1), (RW, 10, 1), (GX, 0, 8),
+        (GW, 11, 1), (GW, 10, 1), (BX, 0, 8), (BW, 11, 1), (BW, 10, 1),
+    ] },
+    Mode { two_regions: false, transformed: true, endpoint_bits: 16, delta_bits: [4, 4, 4], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 4), (RW, 15, 1), (RW, 14, 1), (RW, 13, 1),
+        (RW, 12, 1), (RW, 11, 1), (RW, 10, 1), (GX, 0, 4), (GW, 15, 1), (GW, 14, 1), (GW, 13, 1),
+        (GW, 12, 1), (GW, 11, 1), (GW, 10, 1), (BX, 0, 4), (BW, 15, 1), (BW, 14, 1), (BW, 13, 1),
+        (BW, 12, 1), (BW, 11, 1), (BW, 10, 1),
+    ] },
+];
+
+/// Decodes a BC6H block into 16 linear RGB colors. Reserved modes decode as black.
+pub fn bc6h(block: &[u8], signed: bool) -> [[f32; 3]; 16] {
+    let mut colors = [[0.0; 3]; 16];
+    let mut bits = Bits::new(block);
+
+    let mode_index = match bits.read(2) {
+        mode @ (0 | 1) => mode as usize,
+        low => match (bits.read(3) << 2) | low {
+            0b00010 => 2,
+            0b00110 => 3,
+            0b01010 => 4,
+            0b01110 => 5,
+            0b10010 => 6,
+            0b10110 => 7,
+            0b11010 => 8,
+            0b11110 => 9,
+            0b00011 => 10,
+            0b00111 => 11,
+            0b01011 => 12,
+            0b01111 => 13,
+            _ => return colors,
+        },
+    };
+    let mode = &MODES[mode_index];
+
+    let mut endpoints = [[0i32; 3]; 4];
+    for &((endpoint, channel), shift, count) in mode.layout {
+        endpoints[endpoint][channel] |= (bits.read(count) << shift) as i32;
+    }
+
+    let endpoint_count = if mode.two_regions { 4 } else { 2 };
+    let endpoint_bits = mode.endpoint_bits;
+
+    if signed {
+        for channel in &mut endpoints[0] {
+            *channel = sign_extend(*channel, endpoint_bits);
+        }
+    }
+    if signed || mode.transformed {
+        for endpoint in &mut endpoints[1..endpoint_count] {
+            for (channel, delta_bits) in endpoint.iter_mut().zip(mode.delta_bits) {
+                *channel = sign_extend(*channel, delta_bits);
+            }
+        }
+    }
+    if mode.transformed {
+        let mask = (1 << endpoint_bits) - 1;
+        let base = endpoints[0];
+
+        for endpoint in &mut endpoints[1..endpoint_count] {
+            for (channel, base) in endpoint.iter_mut().zip(base) {
+                *channel = (*channel + base) & mask;
+                if signed {
+                    *channel = sign_extend(*channel, endpoint_bits);
+                }
+            }
+        }
+    }
+
+    for endpoint in &mut endpoints[..endpoint_count] {
+        for channel in endpoint {
+            *channel = unquantize(*channel, endpoint_bits, signed);
+        }
+    }
+
+    let (partition, index_bits) = if mode.two_regions {
+        (bits.read(5) as usize, 3)
+    } else {
+        (0, 4)
+    };
+
+    for (i, color) in colors.iter_mut().enumerate() {
+        let is_anchor = i == 0 || (mode.two_regions && i == ANCHORS_2[partition]);
+        let index = bits.read(index_bits - is_anchor as u32);
+        let region = if mode.two_regions {
+            ((PARTITIONS_2[partition] >> i) & 1) as usize
+        } else {
+            0
+        };
+
+        let weight = weight(index, index_bits) as i32;
+        let (e0, e1) = (endpoints[region * 2], endpoints[region * 2 + 1]);
+
+        for channel in 0..3 {
+            let value = ((64 - weight) * e0[channel] + weight * e1[channel] + 32) >> 6;
+
+            color[channel] = half_to_f32(finish_unquantize(value, signed));
+        }
+    }
+
+    colors
+}
+
+fn sign_extend(value: i32, bits: u32) -> i32 {
+    let shift = 32 - bits;
+
+    (value << shift) >> shift
+}
+
+fn unquantize(value: i32, bits: u32, signed: bool) -> i32 {
+    if !signed {
+        return match value {
+            _ if bits >= 15 => value,
+            0 => 0,
+            _ if value == (1 << bits) - 1 => 0xFFFF,
+            _ => ((value << 16) + 0x8000) >> bits,
+        };
+    }
+
+    if bits >= 16 {
+        return value;
+    }
+
+    let magnitude = value.abs();
+    let unquantized = match magnitude {
+        0 => 0,
+        _ if magnitude >= (1 << (bits - 1)) - 1 => 0x7FFF,
+        _ => ((magnitude << 15) + 0x4000) >> (bits - 1),
+    };
+
+    if value < 0 { -unquantized } else { unquantized }
+}
+
+/// Scales an interpolated value to the bit pattern of a half float.
+fn finish_unquantize(value: i32, signed: bool) -> u16 {
+    if !signed {
+        return ((value * 31) >> 6) as u16;
+    }
+
+    if value < 0 {
+        0x8000 | (((-value) * 31) >> 5) as u16
+    } else {
+        ((value * 31) >> 5) as u16
+    }
+}
+
+fn half_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1F) as i32;
+    let mantissa = (half & 0x3FF) as f32;
+
+    let magnitude = match exponent {
+        0 => mantissa * (-24f32).exp2(),
+        0x1F if mantissa == 0.0 => f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * ((exponent - 15) as f32).exp2(),
+    };
+
+    sign * magnitude
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks a block against the half floats decoded by Mesa's llvmpipe.
+    fn check(block: [u8; 16], signed: bool, expected: [u16; 48]) {
+        let colors = bc6h(&block, signed);
+
+        assert_eq!(colors.as_flattened(), expected.map(half_to_f32));
+    }
+
+    /// Mode 1, with 10-bit endpoints and 5-bit deltas.
+    #[rustfmt::skip]
+    #[test]
+    fn two_regions_transformed() {
+        check(
+            [0x44, 0x7C, 0x76, 0x9F, 0x39, 0xD8, 0x64, 0x41, 0x99, 0xC0, 0xE5, 0xBD, 0xBC, 0xFB, 0xC8, 0x5B],
+            false,
+            [
+                0x788C, 0x5ABD, 0x1929, 0x7927, 0x5B43, 0x1955, 0x78C9, 0x5AF1, 0x193A, 0x7946, 0x5B5D, 0x195E,
+                0x78EC, 0x5B8C, 0x198D, 0x791C, 0x5B46, 0x19B5, 0x788C, 0x5C17, 0x193F, 0x78BC, 0x5BD1, 0x1966,
+                0x78EC, 0x5B8C, 0x198D, 0x788C, 0x5C17, 0x193F, 0x79B1, 0x5A6D, 0x1A2F, 0x7981, 0x5AB3, 0x1A08,
+                0x78BC, 0x5BD1, 0x1966, 0x7951, 0x5AF8, 0x19E0, 0x7951, 0x5AF8, 0x19E0, 0x79B1, 0x5A6D, 0x1A2F,
+            ],
+        );
+    }
+
+    /// Mode 4, with an 11-bit base and deltas of uneven widths.
+    #[rustfmt::skip]
+    #[test]
+    fn two_regions_uneven_deltas() {
+        check(
+            [0x66, 0xE2, 0x0B, 0xF5, 0x70, 0xFA, 0xFB, 0x32, 0x6E, 0xA5, 0xCE, 0xF7, 0x2D, 0x2D, 0x0B, 0x22],
+            false,
+            [
+                0x2FA1, 0x2065, 0x078B, 0x2F9C, 0x2063, 0x0797, 0x2F8F, 0x205C, 0x07B8, 0x2F97, 0x2061, 0x07A2,
+                0x2F51, 0x2154, 0x07D7, 0x2F97, 0x2061, 0x07A2, 0x2F97, 0x2061, 0x07A2, 0x2F9C, 0x2063, 0x0797,
+                0x2FE2, 0x213E, 0x0794, 0x2F89, 0x214C, 0x07BD, 0x2F9C, 0x2063, 0x0797, 0x2F97, 0x2061, 0x07A2,
+                0x301A, 0x2135, 0x077A, 0x2FA6, 0x2147, 0x07B0, 0x301A, 0x2135, 0x077A, 0x2FA9, 0x206A, 0x0775,
+            ],
+        );
+    }
+
+    /// Mode 10, with four explicit 6-bit endpoints.
+    #[rustfmt::skip]
+    #[test]
+    fn two_regions_untransformed() {
+        check(
+            [0xFE, 0x6D, 0xFC, 0x62, 0x39, 0xDF, 0xFF, 0x52, 0xF5, 0x2B, 0xE1, 0x1B, 0x11, 0xB8, 0xD6, 0xD7],
+            false,
+            [
+                0x5C08, 0x6D78, 0x5FE8, 0x4EB6, 0x7775, 0x4BED, 0x2D88, 0x3D08, 0x1C18, 0x3711, 0x4164, 0x2815,
+                0x5C08, 0x6D78, 0x5FE8, 0x67CE, 0x57AC, 0x655B, 0x5E45, 0x5350, 0x595E, 0x7158, 0x5C08, 0x7158,
+                0x4A23, 0x4A1C, 0x400F, 0x54BC, 0x4EF4, 0x4D61, 0x409A, 0x45C0, 0x3412, 0x409A, 0x45C0, 0x3412,
+                0x3711, 0x4164, 0x2815, 0x2D88, 0x3D08, 0x1C18, 0x5E45, 0x5350, 0x595E, 0x54BC, 0x4EF4, 0x4D61,
+            ],
+        );
+    }
+
+    /// Mode 11, with two explicit 10-bit endpoints.
+    #[rustfmt::skip]
+    #[test]
+    fn one_region_untransformed() {
+        check(
+            [0x03, 0xAC, 0x17, 0x1E, 0x81, 0xA5, 0xF8, 0xFA, 0x97, 0x99, 0xDA, 0x98, 0xE7, 0x11, 0x4C, 0xF6],
+            false,
+            [
+                0x265B, 0x1C55, 0x26C6, 0x1E08, 0x47C1, 0x4FED, 0x1E08, 0x47C1, 0x4FED, 0x1E08, 0x47C1, 0x4FED,
+                0x1C5D, 0x5070, 0x5828, 0x185E, 0x6548, 0x6BE9, 0x1F5D, 0x40CE, 0x4958, 0x1E08, 0x47C1, 0x4FED,
+                0x20B2, 0x39DC, 0x42C2, 0x16B4, 0x6DF7, 0x7424, 0x295A, 0x0CB3, 0x17F6, 0x295A, 0x0CB3, 0x17F6,
+                0x19B3, 0x5E55, 0x6554, 0x2506, 0x2347, 0x2D5C, 0x2207, 0x32E9, 0x3C2D, 0x155F, 0x74EA, 0x7ABA,
+            ],
+        );
+    }
+
+    /// Mode 14, with a 16-bit base and 4-bit deltas.
+    #[rustfmt::skip]
+    #[test]
+    fn one_region_transformed() {
+        check(
+            [0x6F, 0x4A, 0x8F, 0xA6, 0xEC, 0x13, 0x0A, 0x65, 0xFB, 0x3E, 0xC0, 0x2C, 0x31, 0x73, 0x59, 0x32],
+            false,
+            [
+                0x6F8F, 0x4E0A, 0x1A4F, 0x6F8E, 0x4E0A, 0x1A4D, 0x6F8E, 0x4E0A, 0x1A4D, 0x6F8F, 0x4E0A, 0x1A4F,
+                0x6F90, 0x4E0A, 0x1A50, 0x6F8F, 0x4E0A, 0x1A4D, 0x6F8F, 0x4E0A, 0x1A4D, 0x6F90, 0x4E0A, 0x1A4F,
+                0x6F90, 0x4E0A, 0x1A50, 0x6F8F, 0x4E0A, 0x1A4F, 0x6F8F, 0x4E0A, 0x1A4F, 0x6F8F, 0x4E0A, 0x1A4E,
+                0x6F8F, 0x4E0A, 0x1A4E, 0x6F8F, 0x4E0A, 0x1A4F, 0x6F90, 0x4E0A, 0x1A4F, 0x6F8F, 0x4E0A, 0x1A4F,
+            ],
+        );
+    }
+
+    /// Mode 2 with signed endpoints.
+    #[rustfmt::skip]
+    #[test]
+    fn signed_two_regions() {
+        check(
+            [0x01, 0x17, 0x05, 0x89, 0x86, 0xD0, 0x87, 0x00, 0x70, 0x39, 0x85, 0x7A, 0xBA, 0x9E, 0xA5, 0xF8],
+            true,
+            [
+                0x4EAE, 0x13CC, 0xF4F2, 0x6D78, 0x1458, 0xF538, 0x559D, 0x216C, 0x2989, 0x53FA, 0x1AE2, 0x4898,
+                0x111A, 0x12B5, 0xF466, 0x5AB3, 0x35C4, 0xB719, 0x5258, 0x1458, 0x67A8, 0x5AB3, 0x35C4, 0xB719,
+                0x5258, 0x1458, 0x67A8, 0x5C55, 0x3C4E, 0xD628, 0x5910, 0x2F3A, 0x9809, 0x5C55, 0x3C4E, 0xD628,
+                0x559D, 0x216C, 0x2989, 0x5DF8, 0x42D8, 0xF538, 0x5258, 0x1458, 0x67A8, 0x5910, 0x2F3A, 0x9809,
+            ],
+        );
+    }
+
+    /// Mode 12 with signed endpoints.
+    #[rustfmt::skip]
+    #[test]
+    fn signed_one_region() {
+        check(
+            [0x67, 0x55, 0x58, 0xA1, 0xDD, 0xA4, 0x1A, 0x32, 0xBB, 0xF5, 0x44, 0x0C, 0x8C, 0x53, 0xA7, 0xD7],
+            true,
+            [
+                0x58ED, 0x5BD6, 0xA0E6, 0x608D, 0x6651, 0x9BFA, 0x58ED, 0x5BD6, 0xA0E6, 0x6589, 0x6D2A, 0x98C3,
+                0x57C1, 0x5A39, 0xA1A8, 0x57C1, 0x5A39, 0xA1A8, 0x61B9, 0x67ED, 0x9B39, 0x52C4, 0x535F, 0xA4DF,
+                0x61B9, 0x67ED, 0x9B39, 0x5CBD, 0x6113, 0x9E70, 0x5695, 0x589D, 0xA269, 0x58ED, 0x5BD6, 0xA0E6,
+                0x5B90, 0x5F76, 0x9F32, 0x5F61, 0x64B4, 0x9CBC, 0x5B90, 0x5F76, 0x9F32, 0x62E6, 0x698A, 0x9A77,
+            ],
+        );
+    }
+
+    #[test]
+    fn reserved_mode() {
+        let mut block = [0xFF; 16];
+        block[0] = 0b10011;
+
+        assert_eq!(bc6h(&block, false), [[0.0; 3]; 16]);
+    }
+}
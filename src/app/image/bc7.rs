@@ -0,0 +1,413 @@
+struct Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index_bits2: u32,
+}
+
+#[allow(clippy::too_many_arguments)]
+const fn mode(
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index_bits2: u32,
+) -> Mode {
+    Mode {
+        subsets,
+        partition_bits,
+        rotation_bits,
+        index_selection_bits,
+        color_bits,
+        alpha_bits,
+        endpoint_pbits,
+        shared_pbits,
+        index_bits,
+        index_bits2,
+    }
+}
+
+#[rustfmt::skip]
+const MODES: [Mode; 8] = [
+    mode(3, 4, 0, 0, 4, 0, true, false, 3, 0),
+    mode(2, 6, 0, 0, 6, 0, false, true, 3, 0),
+    mode(3, 6, 0, 0, 5, 0, false, false, 2, 0),
+    mode(2, 6, 0, 0, 7, 0, true, false, 2, 0),
+    mode(1, 0, 2, 1, 5, 6, false, false, 2, 3),
+    mode(1, 0, 2, 0, 7, 8, false, false, 2, 2),
+    mode(1, 0, 0, 0, 7, 7, true, false, 4, 0),
+    mode(2, 6, 0, 0, 5, 5, true, false, 2, 0),
+];
+
+/// Subset of each pixel for the 2-subset partitions, one bit per pixel.
+#[rustfmt::skip]
+pub const PARTITIONS_2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80,
+    0xC800, 0xFFEC, 0xFE80, 0xE800, 0xFFE8, 0xFF00, 0xFFF0, 0xF000,
+    0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE,
+    0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0, 0x718E, 0x399C,
+    0xAAAA, 0xF0F0, 0x5A5A, 0x33CC, 0x3C3C, 0x55AA, 0x9696, 0xA55A,
+    0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996, 0xC33C, 0x9966, 0x0660,
+    0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C,
+    0x9336, 0x9CC6, 0x817E, 0xE718, 0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+/// Subset of each pixel for the 3-subset partitions, two bits per pixel.
+#[rustfmt::skip]
+const PARTITIONS_3: [u32; 64] = [
+    0xAA685050, 0x6A5A5040, 0x5A5A4200, 0x5450A0A8, 0xA5A50000, 0xA0A05050, 0x5555A0A0, 0x5A5A5050,
+    0xAA550000, 0xAA555500, 0xAAAA5500, 0x90909090, 0x94949494, 0xA4A4A4A4, 0xA9A59450, 0x2A0A4250,
+    0xA5945040, 0x0A425054, 0xA5A5A500, 0x55A0A0A0, 0xA8A85454, 0x6A6A4040, 0xA4A45000, 0x1A1A0500,
+    0x0050A4A4, 0xAAA59090, 0x14696914, 0x69691400, 0xA08585A0, 0xAA821414, 0x50A4A450, 0x6A5A0200,
+    0xA9A58000, 0x5090A0A8, 0xA8A09050, 0x24242424, 0x00AA5500, 0x24924924, 0x24499224, 0x50A50A50,
+    0x500AA550, 0xAAAA4444, 0x66660000, 0xA5A0A5A0, 0x50A050A0, 0x69286928, 0x44AAAA44, 0x66666600,
+    0xAA444444, 0x54A854A8, 0x95809580, 0x96969600, 0xA85454A8, 0x80959580, 0xAA141414, 0x96960000,
+    0xAAAA1414, 0xA05050A0, 0xA0A5A5A0, 0x96000000, 0x40804080, 0xA9A8A9A8, 0xAAAAAA44, 0x2A4A5254,
+];
+
+/// Anchor pixel of the second subset for the 2-subset partitions.
+#[rustfmt::skip]
+pub const ANCHORS_2: [usize; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    15,  2,  8,  2,  2,  8,  8, 15,  2,  8,  2,  2,  8,  8,  2,  2,
+    15, 15,  6,  8,  2,  8, 15, 15,  2,  8,  2,  2,  2, 15, 15,  6,
+     6,  2,  6,  8, 15, 15,  2,  2, 15, 15, 15, 15, 15,  2,  2, 15,
+];
+
+/// Anchor pixel of the second subset for the 3-subset partitions.
+#[rustfmt::skip]
+const ANCHORS_3A: [usize; 64] = [
+     3,  3, 15, 15,  8,  3, 15, 15,  8,  8,  6,  6,  6,  5,  3,  3,
+     3,  3,  8, 15,  3,  3,  6, 10,  5,  8,  8,  6,  8,  5, 15, 15,
+     8, 15,  3,  5,  6, 10,  8, 15, 15,  3, 15,  5, 15, 15, 15, 15,
+     3, 15,  5,  5,  5,  8,  5, 10,  5, 10,  8, 13, 15, 12,  3,  3,
+];
+
+/// Anchor pixel of the third subset for the 3-subset partitions.
+#[rustfmt::skip]
+const ANCHORS_3B: [usize; 64] = [
+    15,  8,  8,  3, 15, 15,  3,  8, 15, 15, 15, 15, 15, 15, 15,  8,
+    15,  8, 15,  3, 15,  8, 15,  8,  3, 15,  6, 10, 15, 15, 10,  8,
+    15,  3, 15, 10, 10,  8,  9, 10,  6, 15,  8, 15,  3,  6,  6,  8,
+    15,  3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,  3, 15, 15,  8,
+];
+
+const WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// Reads a 128-bit block from the least significant bit up.
+pub struct Bits {
+    value: u128,
+}
+
+impl Bits {
+    pub fn new(block: &[u8]) -> Self {
+        Self {
+            value: u128::from_le_bytes(block[..16].try_into().unwrap()),
+        }
+    }
+
+    pub fn read(&mut self, count: u32) -> u32 {
+        let value = self.value & ((1 << count) - 1);
+        self.value >>= count;
+
+        value as u32
+    }
+}
+
+/// Interpolation weight out of 64 for a 2, 3 or 4-bit index, shared with BC6H.
+pub fn weight(index: u32, index_bits: u32) -> u32 {
+    match index_bits {
+        2 => WEIGHTS_2[index as usize],
+        3 => WEIGHTS_3[index as usize],
+        _ => WEIGHTS_4[index as usize],
+    }
+}
+
+fn interpolate(e0: u32, e1: u32, index: u32, index_bits: u32) -> u32 {
+    let weight = weight(index, index_bits);
+
+    ((64 - weight) * e0 + weight * e1 + 32) >> 6
+}
+
+/// Decodes a BC7 block into 16 RGBA pixels. Reserved mode 8 decodes as
+/// transparent black.
+pub fn bc7(block: &[u8], rgba: &mut [u8]) {
+    let mode_index = block[0].trailing_zeros();
+    if mode_index >= 8 {
+        rgba.fill(0);
+        return;
+    }
+
+    let mode = &MODES[mode_index as usize];
+    let mut bits = Bits::new(block);
+    bits.read(mode_index + 1);
+
+    let partition = bits.read(mode.partition_bits) as usize;
+    let rotation = bits.read(mode.rotation_bits);
+    let index_selection = bits.read(mode.index_selection_bits);
+
+    let endpoint_count = mode.subsets * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+
+    for channel in 0..3 {
+        for endpoint in &mut endpoints[..endpoint_count] {
+            endpoint[channel] = bits.read(mode.color_bits);
+        }
+    }
+    for endpoint in &mut endpoints[..endpoint_count] {
+        endpoint[3] = bits.read(mode.alpha_bits);
+    }
+
+    let pbit_count = (mode.endpoint_pbits || mode.shared_pbits) as u32;
+
+    if mode.endpoint_pbits {
+        for endpoint in &mut endpoints[..endpoint_count] {
+            let pbit = bits.read(1);
+            endpoint.iter_mut().for_each(|c| *c = (*c << 1) | pbit);
+        }
+    } else if mode.shared_pbits {
+        for pair in endpoints[..endpoint_count].chunks_exact_mut(2) {
+            let pbit = bits.read(1);
+            pair.iter_mut()
+                .flatten()
+                .for_each(|c| *c = (*c << 1) | pbit);
+        }
+    }
+
+    for endpoint in &mut endpoints[..endpoint_count] {
+        for channel in &mut endpoint[..3] {
+            *channel = expand(*channel, mode.color_bits + pbit_count);
+        }
+        endpoint[3] = match mode.alpha_bits {
+            0 => 255,
+            alpha_bits => expand(endpoint[3], alpha_bits + pbit_count),
+        };
+    }
+
+    let subset = |i: usize| match mode.subsets {
+        2 => ((PARTITIONS_2[partition] >> i) & 1) as usize,
+        3 => ((PARTITIONS_3[partition] >> (i * 2)) & 3) as usize,
+        _ => 0,
+    };
+    let is_anchor = |i: usize| match mode.subsets {
+        2 => i == 0 || i == ANCHORS_2[partition],
+        3 => i == 0 || i == ANCHORS_3A[partition] || i == ANCHORS_3B[partition],
+        _ => i == 0,
+    };
+
+    let mut indices = [0; 16];
+    for (i, index) in indices.iter_mut().enumerate() {
+        *index = bits.read(mode.index_bits - is_anchor(i) as u32);
+    }
+
+    let mut indices2 = [0; 16];
+    if mode.index_bits2 > 0 {
+        for (i, index) in indices2.iter_mut().enumerate() {
+            *index = bits.read(mode.index_bits2 - (i == 0) as u32);
+        }
+    }
+
+    for i in 0..16 {
+        let s = subset(i);
+        let (e0, e1) = (endpoints[s * 2], endpoints[s * 2 + 1]);
+
+        let (color_index, color_bits, alpha_index, alpha_bits) = match mode.index_bits2 {
+            0 => (indices[i], mode.index_bits, indices[i], mode.index_bits),
+            _ if index_selection == 0 => {
+                (indices[i], mode.index_bits, indices2[i], mode.index_bits2)
+            }
+            _ => (indices2[i], mode.index_bits2, indices[i], mode.index_bits),
+        };
+
+        let mut color = [0u8; 4];
+        for channel in 0..3 {
+            color[channel] = interpolate(e0[channel], e1[channel], color_index, color_bits) as u8;
+        }
+        color[3] = interpolate(e0[3], e1[3], alpha_index, alpha_bits) as u8;
+
+        match rotation {
+            1 => color.swap(0, 3),
+            2 => color.swap(1, 3),
+            3 => color.swap(2, 3),
+            _ => {}
+        }
+
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&color);
+    }
+}
+
+/// Expands a `bits` wide value to 8 bits by replicating its high bits.
+fn expand(value: u32, bits: u32) -> u32 {
+    let value = value << (8 - bits);
+
+    value | (value >> bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks a block against the pixels decoded by a C port of bcdec.
+    fn check(block: [u8; 16], expected: [u8; 64]) {
+        let mut rgba = [0; 64];
+        bc7(&block, &mut rgba);
+
+        assert_eq!(rgba, expected);
+    }
+
+    /// Mode 0, partition 13.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_0_three_subsets() {
+        check(
+            [0x3B, 0xB4, 0xE6, 0x52, 0xE4, 0x4D, 0xA7, 0xF2, 0x37, 0x0D, 0x9E, 0x26, 0x0E, 0x27, 0x13, 0x65],
+            [
+                 79,  33, 110, 255,  63, 161, 209, 255, 151,  73, 114, 255, 142, 106, 128, 255,
+                 16,  33,  82, 255,  68, 185, 219, 255, 128, 157, 149, 255, 142, 106, 128, 255,
+                 79,  33, 110, 255,  73, 205, 228, 255, 151,  73, 114, 255, 142, 106, 128, 255,
+                 16,  33,  82, 255,  58, 141, 200, 255, 142, 106, 128, 255, 128, 157, 149, 255,
+            ],
+        );
+    }
+
+    /// Mode 1, partition 37.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_1_shared_pbits() {
+        check(
+            [0x96, 0xA4, 0xA3, 0xA6, 0xD0, 0x7F, 0x5C, 0x0C, 0x33, 0x2F, 0x8B, 0x12, 0x24, 0x08, 0x3F, 0xD2],
+            [
+                122, 119,  50, 255, 171,  30, 207, 255,  83, 202,  50, 255, 169,  67, 114, 255,
+                147,  66,  50, 255, 169,  67, 114, 255,  96, 175,  50, 255, 171,  30, 207, 255,
+                171,  30, 207, 255, 134,  93,  50, 255, 169,  67, 114, 255,  58, 255,  50, 255,
+                169,  57, 139, 255,  96, 175,  50, 255, 169,  67, 114, 255,  71, 228,  50, 255,
+            ],
+        );
+    }
+
+    /// Mode 2, partition 50.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_2_three_subsets() {
+        check(
+            [0x94, 0x91, 0x2F, 0x89, 0x11, 0xE8, 0x18, 0x18, 0xF8, 0xC9, 0x9D, 0x5D, 0x5D, 0x98, 0x31, 0x95],
+            [
+                125, 126, 131, 255, 125, 126, 131, 255, 125, 126, 131, 255,  99,  24, 115, 255,
+                 63,  32, 211, 255,  52,  67, 217, 255,  41,  99, 222, 255,  33, 198, 173, 255,
+                 66, 132, 123, 255, 188, 121, 140, 255, 125, 126, 131, 255,  55, 141, 154, 255,
+                 63,  32, 211, 255,  63,  32, 211, 255,  41,  99, 222, 255,  77,  81, 134, 255,
+            ],
+        );
+    }
+
+    /// Mode 3, partition 17.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_3_endpoint_pbits() {
+        check(
+            [0x18, 0x05, 0xD9, 0x0E, 0x94, 0x5D, 0xE2, 0xE8, 0xF5, 0x4E, 0xE7, 0x81, 0xCC, 0x75, 0xF6, 0x36],
+            [
+                159, 171, 108, 255,  45,  59, 140, 255,  28,  28, 206, 255,  80, 122,   6, 255,
+                159, 171, 108, 255, 159, 171, 108, 255, 217,  37,  79, 255,  45,  59, 140, 255,
+                188, 102,  93, 255, 159, 171, 108, 255, 217,  37,  79, 255, 217,  37,  79, 255,
+                188, 102,  93, 255, 159, 171, 108, 255, 217,  37,  79, 255, 130, 236, 122, 255,
+            ],
+        );
+    }
+
+    /// Mode 4, rotation 2, index selection 1.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_4_rotation_and_index_selection() {
+        check(
+            [0xD0, 0x50, 0x99, 0x09, 0x5A, 0xA3, 0x00, 0x16, 0x5A, 0x67, 0x03, 0x6F, 0x9B, 0x54, 0x0D, 0x6B],
+            [
+                125,  52,  15,  64, 132,  52,   0,  49, 103,  52,  62, 111,  82,  52, 107, 156,
+                 89,  40,  92, 141,  89,  44,  92, 141,  89,  52,  92, 141, 103,  52,  62, 111,
+                103,  48,  62, 111, 118,  40,  30,  79,  96,  44,  77, 126,  89,  44,  92, 141,
+                132,  40,   0,  49,  89,  52,  92, 141, 118,  40,  30,  79, 111,  44,  45,  94,
+            ],
+        );
+    }
+
+    /// Mode 4, rotation 1, index selection 0.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_4_without_index_selection() {
+        check(
+            [0x30, 0x0B, 0xE2, 0x11, 0x24, 0x17, 0x9C, 0x3D, 0xD9, 0xF7, 0x38, 0x17, 0xCE, 0x6E, 0x11, 0x8D],
+            [
+                113, 141,  54, 104,   4,  24, 148, 132,  50, 198,   8,  90,  67,  24, 148, 132,
+                 98,  81, 102, 118,  50,  24, 148, 132,  67, 141,  54, 104,  19,  81, 102, 118,
+                 19, 198,   8,  90,  35,  24, 148, 132,  35,  81, 102, 118, 113,  24, 148, 132,
+                 98,  24, 148, 132,  82,  81, 102, 118,  67,  24, 148, 132,  50, 141,  54, 104,
+            ],
+        );
+    }
+
+    /// Mode 5, rotation 3.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_5_rotation() {
+        check(
+            [0xE0, 0x4A, 0xAD, 0x6C, 0xB6, 0xDD, 0x21, 0x0F, 0xAF, 0x94, 0xAC, 0xD3, 0xCF, 0x92, 0xC1, 0x90],
+            [
+                160, 101, 198, 162, 160, 101, 195, 162, 160, 101, 200, 162, 160, 101, 195, 162,
+                171, 101, 197, 139, 171, 101, 200, 139, 149, 100, 198, 183, 160, 101, 197, 162,
+                171, 101, 198, 139, 160, 101, 200, 162, 160, 101, 200, 162, 181, 102, 195, 118,
+                160, 101, 200, 162, 171, 101, 200, 139, 171, 101, 198, 139, 181, 102, 197, 118,
+            ],
+        );
+    }
+
+    /// Mode 6.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_6_four_bit_indices() {
+        check(
+            [0x40, 0x7C, 0xB1, 0x1F, 0x5D, 0x10, 0x8C, 0xF2, 0x59, 0x30, 0x26, 0x39, 0x38, 0xB3, 0x70, 0xA1],
+            [
+                214, 228,  19, 164, 208, 222,  18, 170, 241, 251,  23, 141, 220, 233,  20, 159,
+                200, 215,  17, 177, 227, 239,  21, 153, 180, 199,  15, 193, 220, 233,  20, 159,
+                187, 204,  16, 188, 220, 233,  20, 159, 220, 233,  20, 159, 166, 186,  13, 206,
+                241, 251,  23, 141, 193, 210,  16, 182, 235, 246,  22, 147, 172, 192,  14, 200,
+            ],
+        );
+    }
+
+    /// Mode 7, partition 5.
+    #[rustfmt::skip]
+    #[test]
+    fn mode_7_alpha_subsets() {
+        check(
+            [0x80, 0x45, 0x9F, 0xA0, 0xF1, 0x48, 0x3F, 0x95, 0xA9, 0x0D, 0x9D, 0xF2, 0xF1, 0x30, 0xD6, 0x0F],
+            [
+                239, 231, 166,  28, 185, 171, 155, 154, 105, 251, 105, 203,  37, 194,  86, 118,
+                239, 231, 166,  28,  72, 223,  96, 162,  37, 194,  86, 118,   4, 166,  77,  77,
+                158, 142, 150, 215,  72, 223,  96, 162,  72, 223,  96, 162, 105, 251, 105, 203,
+                105, 251, 105, 203,  37, 194,  86, 118,   4, 166,  77,  77,   4, 166,  77,  77,
+            ],
+        );
+    }
+
+    #[test]
+    fn reserved_mode() {
+        let mut rgba = [1; 64];
+        bc7(&[0; 16], &mut rgba);
+
+        assert_eq!(rgba, [0; 64]);
+    }
+}
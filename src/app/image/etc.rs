@@ -0,0 +1,539 @@
+use super::bc::normal_z;
+
+#[rustfmt::skip]
+const MODIFIERS: [[i32; 4]; 8] = [
+    [2, 8, -2, -8],
+    [5, 17, -5, -17],
+    [9, 29, -9, -29],
+    [13, 42, -13, -42],
+    [18, 60, -18, -60],
+    [24, 80, -24, -80],
+    [33, 106, -33, -106],
+    [47, 183, -47, -183],
+];
+
+const DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+#[rustfmt::skip]
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+/// Decodes an ETC1 block into 16 RGBA pixels.
+pub fn etc1(block: &[u8], rgba: &mut [u8]) {
+    color_block(block, rgba, false, false);
+}
+
+/// Decodes an ETC2 RGB block, including the T, H and planar modes.
+pub fn etc2_rgb(block: &[u8], rgba: &mut [u8]) {
+    color_block(block, rgba, true, false);
+}
+
+/// Decodes an ETC2 RGB block with punch-through alpha.
+pub fn etc2_rgba1(block: &[u8], rgba: &mut [u8]) {
+    color_block(block, rgba, true, true);
+}
+
+/// Decodes an ETC2 RGBA block, which stores an EAC alpha block before the color.
+pub fn etc2_rgba8(block: &[u8], rgba: &mut [u8]) {
+    color_block(&block[8..16], rgba, true, false);
+
+    let (base, multiplier, modifiers, indices) = eac_params(&block[..8]);
+
+    for i in 0..16 {
+        let modifier = modifiers[eac_index(indices, i)];
+        let alpha = (base as i32 + modifier * multiplier).clamp(0, 255);
+
+        rgba[pixel_offset(i) + 3] = alpha as u8;
+    }
+}
+
+/// Decodes a 3DS ETC1 block, which stores the 64-bit ETC1 word little-endian.
+pub fn etc1_3ds(block: &[u8], rgba: &mut [u8]) {
+    let mut bytes: [u8; 8] = block[..8].try_into().unwrap();
+    bytes.reverse();
+
+    etc1(&bytes, rgba);
+}
+
+/// Decodes a 3DS ETC1A4 block, which stores 4-bit alpha in column-major order
+/// before a 3DS ETC1 block.
+pub fn etc1a4_3ds(block: &[u8], rgba: &mut [u8]) {
+    etc1_3ds(&block[8..16], rgba);
+
+    let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+
+    for i in 0..16 {
+        rgba[pixel_offset(i) + 3] = ((alpha >> (i * 4)) & 0xF) as u8 * 17;
+    }
+}
+
+/// Decodes an EAC R11 block as grayscale.
+pub fn eac_r11(block: &[u8], rgba: &mut [u8], signed: bool) {
+    let values = eac11(&block[..8], signed);
+
+    for (i, v) in values.into_iter().enumerate() {
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&[v, v, v, 255]);
+    }
+}
+
+/// Decodes an EAC RG11 block into red and green. With `reconstruct_z`, blue is
+/// rebuilt as the Z component of a unit normal.
+pub fn eac_rg11(block: &[u8], rgba: &mut [u8], signed: bool, reconstruct_z: bool) {
+    let xs = eac11(&block[..8], signed);
+    let ys = eac11(&block[8..16], signed);
+
+    for (i, (x, y)) in xs.into_iter().zip(ys).enumerate() {
+        let z = if reconstruct_z { normal_z(x, y) } else { 0 };
+
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&[x, y, z, 255]);
+    }
+}
+
+/// Decodes the 8-byte ETC1/ETC2 color block. `etc2` enables the T, H and planar
+/// modes hidden in overflowing differential colors. With `punch_through`, the diff
+/// bit becomes the opaque bit and the block is always differential.
+fn color_block(block: &[u8], rgba: &mut [u8], etc2: bool, punch_through: bool) {
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap());
+    let diff_bit = (bits >> 33) & 1 == 1;
+    let differential = punch_through || diff_bit;
+    let transparent = punch_through && !diff_bit;
+    let flip = (bits >> 32) & 1 == 1;
+
+    let base = |shift: u32| ((bits >> shift) & 0x1F) as i32;
+    let delta = |shift: u32| ((((bits >> shift) & 0x7) as i32) << 29) >> 29;
+
+    let (base1, base2) = if differential {
+        let r = base(59);
+        let g = base(51);
+        let b = base(43);
+        let r2 = r + delta(56);
+        let g2 = g + delta(48);
+        let b2 = b + delta(40);
+
+        if etc2 && !(0..32).contains(&r2) {
+            return t_mode(bits, rgba, transparent);
+        }
+        if etc2 && !(0..32).contains(&g2) {
+            return h_mode(bits, rgba, transparent);
+        }
+        if etc2 && !(0..32).contains(&b2) {
+            return planar_mode(bits, rgba);
+        }
+
+        (
+            [extend5(r), extend5(g), extend5(b)],
+            [extend5(r2 & 0x1F), extend5(g2 & 0x1F), extend5(b2 & 0x1F)],
+        )
+    } else {
+        let nibble = |shift: u32| ((bits >> shift) & 0xF) as i32 * 17;
+
+        (
+            [nibble(60), nibble(52), nibble(44)],
+            [nibble(56), nibble(48), nibble(40)],
+        )
+    };
+
+    let tables = [((bits >> 37) & 7) as usize, ((bits >> 34) & 7) as usize];
+
+    for i in 0..16 {
+        let (x, y) = (i / 4, i % 4);
+        let second = if flip { y >= 2 } else { x >= 2 };
+        let (base, table) = if second {
+            (base2, tables[1])
+        } else {
+            (base1, tables[0])
+        };
+
+        let index = pixel_index(bits, i);
+        let dst = pixel_offset(i);
+
+        if transparent && index == 2 {
+            rgba[dst..dst + 4].fill(0);
+            continue;
+        }
+
+        let modifier = if transparent && index == 0 {
+            0
+        } else {
+            MODIFIERS[table][index]
+        };
+
+        for channel in 0..3 {
+            rgba[dst + channel] = (base[channel] + modifier).clamp(0, 255) as u8;
+        }
+        rgba[dst + 3] = 255;
+    }
+}
+
+fn t_mode(bits: u64, rgba: &mut [u8], transparent: bool) {
+    let nibble = |shift: u32| ((bits >> shift) & 0xF) as i32 * 17;
+
+    let r1 = ((((bits >> 59) & 0x3) << 2) | ((bits >> 56) & 0x3)) as i32 * 17;
+    let c1 = [r1, nibble(52), nibble(48)];
+    let c2 = [nibble(44), nibble(40), nibble(36)];
+    let distance = DISTANCES[((((bits >> 34) & 0x3) << 1) | ((bits >> 32) & 1)) as usize];
+
+    let paint = [c1, offset(c2, distance), c2, offset(c2, -distance)];
+    fill_paint(bits, rgba, paint, transparent);
+}
+
+fn h_mode(bits: u64, rgba: &mut [u8], transparent: bool) {
+    let nibble = |shift: u32| ((bits >> shift) & 0xF) as i32;
+
+    let r1 = nibble(59);
+    let g1 = ((((bits >> 56) & 0x7) << 1) | ((bits >> 52) & 1)) as i32;
+    let b1 = ((((bits >> 51) & 1) << 3) | ((bits >> 47) & 0x7)) as i32;
+    let (r2, g2, b2) = (nibble(43), nibble(39), nibble(35));
+
+    let order = ((r1 << 8) | (g1 << 4) | b1) >= ((r2 << 8) | (g2 << 4) | b2);
+    let index = (((bits >> 34) & 1) << 2) | (((bits >> 32) & 1) << 1) | order as u64;
+    let distance = DISTANCES[index as usize];
+
+    let c1 = [r1 * 17, g1 * 17, b1 * 17];
+    let c2 = [r2 * 17, g2 * 17, b2 * 17];
+
+    let paint = [
+        offset(c1, distance),
+        offset(c1, -distance),
+        offset(c2, distance),
+        offset(c2, -distance),
+    ];
+    fill_paint(bits, rgba, paint, transparent);
+}
+
+fn planar_mode(bits: u64, rgba: &mut [u8]) {
+    let field = |shift: u32, count: u32| ((bits >> shift) & ((1 << count) - 1)) as i32;
+
+    let ro = extend6(field(57, 6));
+    let go = extend7((field(56, 1) << 6) | field(49, 6));
+    let bo = extend6((field(48, 1) << 5) | (field(43, 2) << 3) | field(39, 3));
+    let rh = extend6((field(34, 5) << 1) | field(32, 1));
+    let gh = extend7(field(25, 7));
+    let bh = extend6(field(19, 6));
+    let rv = extend6(field(13, 6));
+    let gv = extend7(field(6, 7));
+    let bv = extend6(field(0, 6));
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let plane = |o: i32, h: i32, v: i32| {
+                ((x * (h - o) + y * (v - o) + 4 * o + 2) >> 2).clamp(0, 255) as u8
+            };
+            let dst = ((y * 4 + x) * 4) as usize;
+
+            rgba[dst..dst + 4].copy_from_slice(&[
+                plane(ro, rh, rv),
+                plane(go, gh, gv),
+                plane(bo, bh, bv),
+                255,
+            ]);
+        }
+    }
+}
+
+fn fill_paint(bits: u64, rgba: &mut [u8], paint: [[i32; 3]; 4], transparent: bool) {
+    for i in 0..16 {
+        let index = pixel_index(bits, i);
+        let dst = pixel_offset(i);
+
+        if transparent && index == 2 {
+            rgba[dst..dst + 4].fill(0);
+            continue;
+        }
+
+        for channel in 0..3 {
+            rgba[dst + channel] = paint[index][channel] as u8;
+        }
+        rgba[dst + 3] = 255;
+    }
+}
+
+/// Decodes an 11-bit EAC channel. Values are scaled to 8 bits, with signed
+/// channels remapped from -1023..=1023 to 0..=255 so that -1.0 shows as black.
+fn eac11(block: &[u8], signed: bool) -> [u8; 16] {
+    let (base, multiplier, modifiers, indices) = eac_params(block);
+    let mut values = [0; 16];
+
+    for i in 0..16 {
+        let modifier = modifiers[eac_index(indices, i)];
+        let modifier = match multiplier {
+            0 => modifier,
+            _ => modifier * multiplier * 8,
+        };
+        let dst = pixel_offset(i) / 4;
+
+        values[dst] = if signed {
+            let base = (base as i8).max(-127) as i32;
+            let v = (base * 8 + modifier).clamp(-1023, 1023);
+
+            ((v + 1023) * 255 / 2046) as u8
+        } else {
+            let v = (base as i32 * 8 + 4 + modifier).clamp(0, 2047);
+
+            (v * 255 / 2047) as u8
+        };
+    }
+
+    values
+}
+
+/// Splits an EAC block into its base codeword, multiplier, modifier table and
+/// 48 bits of pixel indices.
+fn eac_params(block: &[u8]) -> (u8, i32, &'static [i32; 8], u64) {
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap());
+    let base = (bits >> 56) as u8;
+    let multiplier = ((bits >> 52) & 0xF) as i32;
+    let table = ((bits >> 48) & 0xF) as usize;
+
+    (
+        base,
+        multiplier,
+        &EAC_MODIFIERS[table],
+        bits & 0xFFFF_FFFF_FFFF,
+    )
+}
+
+fn eac_index(indices: u64, i: usize) -> usize {
+    ((indices >> (45 - i * 3)) & 0x7) as usize
+}
+
+/// ETC pixel indices run down the columns, the low 16 bits holding the LSB and
+/// the next 16 bits the MSB of each index.
+fn pixel_index(bits: u64, i: usize) -> usize {
+    ((((bits >> (i + 16)) & 1) << 1) | ((bits >> i) & 1)) as usize
+}
+
+/// RGBA offset of the `i`th pixel in ETC's column-major order.
+fn pixel_offset(i: usize) -> usize {
+    ((i % 4) * 4 + i / 4) * 4
+}
+
+fn offset(color: [i32; 3], distance: i32) -> [i32; 3] {
+    color.map(|c| (c + distance).clamp(0, 255))
+}
+
+fn extend5(value: i32) -> i32 {
+    (value << 3) | (value >> 2)
+}
+
+fn extend6(value: i32) -> i32 {
+    (value << 2) | (value >> 4)
+}
+
+fn extend7(value: i32) -> i32 {
+    (value << 1) | (value >> 6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks a block against the pixels decoded by Mesa's llvmpipe. EAC values
+    /// are Mesa's 11-bit values, scaled to 8 bits the way `eac11` does.
+    fn check(decode: impl Fn(&[u8], &mut [u8]), block: &[u8], expected: [u8; 64]) {
+        let mut rgba = [0; 64];
+        decode(block, &mut rgba);
+
+        assert_eq!(rgba, expected);
+    }
+
+    /// Individual mode, with the subblocks side by side.
+    #[rustfmt::skip]
+    #[test]
+    fn etc1_individual() {
+        check(
+            etc1,
+            &[0x92, 0xE5, 0xDF, 0xE8, 0xCB, 0x18, 0x55, 0xFE],
+            [
+                200, 255, 255, 255,   0,  55,  38, 255,   5,  56, 226, 255,  63, 114, 255, 255,
+                255, 255, 255, 255, 255, 255, 255, 255,  25,  76, 246, 255,  43,  94, 255, 255,
+                255, 255, 255, 255, 255, 255, 255, 255,  63, 114, 255, 255,   5,  56, 226, 255,
+                  0,  55,  38, 255, 255, 255, 255, 255,  25,  76, 246, 255,  25,  76, 246, 255,
+            ],
+        );
+    }
+
+    /// Differential mode, with the subblocks stacked.
+    #[rustfmt::skip]
+    #[test]
+    fn etc1_differential() {
+        check(
+            etc1,
+            &[0xC3, 0x20, 0xA4, 0x73, 0x7C, 0x2B, 0x3A, 0xBE],
+            [
+                185,  20, 152, 255, 240,  75, 207, 255, 211,  46, 178, 255, 156,   0, 123, 255,
+                156,   0, 123, 255, 156,   0, 123, 255, 240,  75, 207, 255, 156,   0, 123, 255,
+                255,  93, 192, 255, 240,  51, 150, 255, 204,  15, 114, 255, 204,  15, 114, 255,
+                162,   0,  72, 255, 255,  93, 192, 255, 162,   0,  72, 255, 240,  51, 150, 255,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn etc2_t_mode() {
+        check(
+            etc2_rgb,
+            &[0xEB, 0xBC, 0xD0, 0xDE, 0x06, 0x3E, 0x4B, 0x37],
+            [
+                255,  41, 255, 255, 180,   0, 180, 255, 255,  41, 255, 255, 119, 187, 204, 255,
+                180,   0, 180, 255, 180,   0, 180, 255, 180,   0, 180, 255, 119, 187, 204, 255,
+                180,   0, 180, 255, 119, 187, 204, 255, 221,   0, 221, 255, 255,  41, 255, 255,
+                221,   0, 221, 255, 119, 187, 204, 255, 255,  41, 255, 255, 119, 187, 204, 255,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn etc2_h_mode() {
+        check(
+            etc2_rgb,
+            &[0x0F, 0x0D, 0xC1, 0x3A, 0xA3, 0x48, 0x39, 0x5E],
+            [
+                 20, 241, 190, 255,  14, 235, 184, 255, 133,  31, 116, 255,  14, 235, 184, 255,
+                 14, 235, 184, 255,  20, 241, 190, 255, 139,  37, 122, 255, 133,  31, 116, 255,
+                 14, 235, 184, 255, 133,  31, 116, 255,  20, 241, 190, 255,  20, 241, 190, 255,
+                133,  31, 116, 255,  20, 241, 190, 255,  14, 235, 184, 255, 139,  37, 122, 255,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn etc2_planar_mode() {
+        check(
+            etc2_rgb,
+            &[0xD3, 0x61, 0x0D, 0xEB, 0xF9, 0xFE, 0x6D, 0xBC],
+            [
+                166, 225, 174, 255, 178, 231, 194, 255, 191, 237, 215, 255, 203, 243, 235, 255,
+                176, 196, 191, 255, 189, 202, 212, 255, 201, 208, 232, 255, 213, 214, 252, 255,
+                187, 167, 209, 255, 199, 173, 229, 255, 211, 179, 249, 255, 223, 185, 255, 255,
+                197, 137, 226, 255, 209, 143, 246, 255, 221, 149, 255, 255, 234, 155, 255, 255,
+            ],
+        );
+    }
+
+    /// Differential mode with the opaque bit clear, so that index 2 is transparent.
+    #[rustfmt::skip]
+    #[test]
+    fn punch_through_differential() {
+        check(
+            etc2_rgba1,
+            &[0xE0, 0x40, 0xB4, 0x58, 0x2B, 0xCC, 0x08, 0xA2],
+            [
+                231,  66, 181, 255, 231,  66, 181, 255,   0,   0,   0,   0, 231,  66, 148, 255,
+                255,  95, 210, 255, 255,  95, 210, 255,   0,   0,   0,   0,   0,   0,   0,   0,
+                  0,   0,   0,   0,   0,   0,   0,   0, 231,  66, 148, 255, 231,  66, 148, 255,
+                  0,   0,   0,   0, 202,  37, 152, 255, 125,   0,  42, 255, 231,  66, 148, 255,
+            ],
+        );
+    }
+
+    /// H mode with the opaque bit clear.
+    #[rustfmt::skip]
+    #[test]
+    fn punch_through_h_mode() {
+        check(
+            etc2_rgba1,
+            &[0xBF, 0x14, 0x41, 0x48, 0x4C, 0xD9, 0x1C, 0x19],
+            [
+                133,  31, 150, 255, 133,  31, 150, 255, 122, 255,   3, 255, 116, 252,   0, 255,
+                122, 255,   3, 255, 122, 255,   3, 255, 122, 255,   3, 255, 122, 255,   3, 255,
+                122, 255,   3, 255,   0,   0,   0,   0, 133,  31, 150, 255,   0,   0,   0,   0,
+                133,  31, 150, 255,   0,   0,   0,   0, 133,  31, 150, 255, 122, 255,   3, 255,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn etc2_rgba8_alpha() {
+        check(
+            etc2_rgba8,
+            &[0x45, 0x79, 0x3D, 0xC0, 0x42, 0xB5, 0x06, 0xBC, 0x73, 0x4A, 0x0B, 0xE2, 0xC9, 0xD4, 0x7E, 0x07],
+            [
+                255, 255, 191,  34,  68,  27,   0,  55, 138,  88,  31,  97, 148,  98,  41,   0,
+                255, 255, 191, 132, 162, 121,  55,  34, 148,  98,  41,  97, 148,  98,  41,  13,
+                  0,   0,   0,   0,  68,  27,   0,  55, 148,  98,  41,  13, 132,  82,  25, 132,
+                162, 121,  55,  76,  68,  27,   0,  13, 132,  82,  25,  55, 138,  88,  31,  76,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn eac_r11_unsigned() {
+        check(
+            |block, rgba| eac_r11(block, rgba, false),
+            &[0x44, 0xD7, 0x03, 0x2F, 0x89, 0x4A, 0x99, 0x84],
+            [
+                 29,  29,  29, 255, 197, 197, 197, 255,   0,   0,   0, 255,  94,  94,  94, 255,
+                 29,  29,  29, 255, 158, 158, 158, 255,   0,   0,   0, 255, 158, 158, 158, 255,
+                158, 158, 158, 255,   3,   3,   3, 255, 120, 120, 120, 255,  29,  29,  29, 255,
+                  0,   0,   0, 255,   3,   3,   3, 255,   3,   3,   3, 255,  94,  94,  94, 255,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn eac_r11_signed() {
+        check(
+            |block, rgba| eac_r11(block, rgba, true),
+            &[0x71, 0x3F, 0x04, 0x11, 0xFF, 0x6F, 0x1C, 0xC8],
+            [
+                231, 231, 231, 255, 231, 231, 231, 255, 213, 213, 213, 255, 255, 255, 255, 255,
+                225, 225, 225, 255, 255, 255, 255, 255, 213, 213, 213, 255, 213, 213, 213, 255,
+                231, 231, 231, 255, 255, 255, 255, 255, 255, 255, 255, 255, 225, 225, 225, 255,
+                225, 225, 225, 255, 255, 255, 255, 255, 225, 225, 225, 255, 231, 231, 231, 255,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn eac_rg11_unsigned() {
+        check(
+            |block, rgba| eac_rg11(block, rgba, false, false),
+            &[0xB6, 0x1C, 0x6D, 0x98, 0x7F, 0x4D, 0xF4, 0xAC, 0xCF, 0xC9, 0xCF, 0xB5, 0x94, 0x79, 0x46, 0xA3],
+            [
+                171, 255,   0, 255, 183, 111,   0, 255, 174,  87,   0, 255, 174,  87,   0, 255,
+                171,  87,   0, 255, 177, 255,   0, 255, 171, 255,   0, 255, 174, 111,   0, 255,
+                171, 255,   0, 255, 190, 111,   0, 255, 171, 111,   0, 255, 184, 218,   0, 255,
+                177,  87,   0, 255, 190, 218,   0, 255, 190, 218,   0, 255, 183,  87,   0, 255,
+            ],
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn eac_rg11_signed() {
+        check(
+            |block, rgba| eac_rg11(block, rgba, true, false),
+            &[0x29, 0xE9, 0x16, 0x5D, 0x81, 0x93, 0x22, 0xFD, 0x3D, 0x4A, 0xA2, 0x4C, 0x15, 0x93, 0x42, 0x4C],
+            [
+                140, 200,   0, 255, 255, 216,   0, 255, 182, 192,   0, 255,  98, 172,   0, 255,
+                224, 180,   0, 255, 255, 180,   0, 255, 182, 192,   0, 255,  28, 172,   0, 255,
+                182, 192,   0, 255, 140, 156,   0, 255, 255, 216,   0, 255, 255, 172,   0, 255,
+                224, 192,   0, 255,  98, 200,   0, 255,  56, 192,   0, 255, 224, 192,   0, 255,
+            ],
+        );
+    }
+}
@@ -0,0 +1,474 @@
+use super::bc::bc1;
+use crate::app::gx_format::GxPaletteFormat;
+use crate::app::image_format::BitOrder;
+
+/// Decodes an 8x8 I4 block, with pixels in `bit_order`. Intensity is also used as
+/// alpha.
+pub fn i4(block: &[u8], rgba: &mut [u8], bit_order: BitOrder) {
+    for (i, &byte) in block[..32].iter().enumerate() {
+        for (j, value) in nibbles(byte, bit_order).into_iter().enumerate() {
+            let dst = (i * 2 + j) * 4;
+
+            rgba[dst..dst + 4].fill(value * 17);
+        }
+    }
+}
+
+/// Decodes an 8x4 I8 block. Intensity is also used as alpha.
+pub fn i8(block: &[u8], rgba: &mut [u8]) {
+    for (i, &value) in block[..32].iter().enumerate() {
+        rgba[i * 4..i * 4 + 4].fill(value);
+    }
+}
+
+/// Decodes an 8x4 IA4 block, with alpha in the high nibble.
+pub fn ia4(block: &[u8], rgba: &mut [u8]) {
+    for (i, &byte) in block[..32].iter().enumerate() {
+        let a = (byte >> 4) * 17;
+        let v = (byte & 0xF) * 17;
+
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&[v, v, v, a]);
+    }
+}
+
+/// Decodes a 4x4 block of 16-bit big-endian pixels, such as IA8, RGB565 and
+/// RGB5A3.
+pub fn color16(block: &[u8], rgba: &mut [u8], format: GxPaletteFormat) {
+    for (i, pixel) in block[..32].chunks_exact(2).enumerate() {
+        let value = u16::from_be_bytes([pixel[0], pixel[1]]);
+
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&color(value, format));
+    }
+}
+
+/// Decodes a 4x4 RGBA32 block, which stores the AR pairs of all pixels before
+/// the GB pairs.
+pub fn rgba32(block: &[u8], rgba: &mut [u8]) {
+    let (ar, gb) = block[..64].split_at(32);
+
+    for i in 0..16 {
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&[
+            ar[i * 2 + 1],
+            gb[i * 2],
+            gb[i * 2 + 1],
+            ar[i * 2],
+        ]);
+    }
+}
+
+/// Decodes an 8x8 C4 block, with indices in `bit_order`.
+pub fn c4(block: &[u8], rgba: &mut [u8], palette: &[[u8; 4]], bit_order: BitOrder) {
+    for (i, &byte) in block[..32].iter().enumerate() {
+        for (j, index) in nibbles(byte, bit_order).into_iter().enumerate() {
+            let dst = (i * 2 + j) * 4;
+
+            rgba[dst..dst + 4].copy_from_slice(&palette[index as usize]);
+        }
+    }
+}
+
+/// Decodes an 8x4 C8 block.
+pub fn c8(block: &[u8], rgba: &mut [u8], palette: &[[u8; 4]]) {
+    for (i, &index) in block[..32].iter().enumerate() {
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&palette[index as usize]);
+    }
+}
+
+/// Decodes a 4x4 C14X2 block of big-endian indices, ignoring the top two bits.
+pub fn c14x2(block: &[u8], rgba: &mut [u8], palette: &[[u8; 4]]) {
+    for (i, pixel) in block[..32].chunks_exact(2).enumerate() {
+        let index = u16::from_be_bytes([pixel[0], pixel[1]]) & 0x3FFF;
+
+        rgba[i * 4..i * 4 + 4].copy_from_slice(&palette[index as usize]);
+    }
+}
+
+/// Decodes an 8x8 CMPR block: four DXT1 sub-blocks in Z order, with big-endian
+/// colors and the leftmost pixel in the high bits of each index byte.
+pub fn cmpr(block: &[u8], rgba: &mut [u8]) {
+    let mut sub_rgba = [0; 16 * 4];
+
+    for (i, sub_block) in block[..32].chunks_exact(8).enumerate() {
+        let mut dxt1 = [0; 8];
+        dxt1[..2].copy_from_slice(&[sub_block[1], sub_block[0]]);
+        dxt1[2..4].copy_from_slice(&[sub_block[3], sub_block[2]]);
+        for (dst, &row) in dxt1[4..].iter_mut().zip(&sub_block[4..]) {
+            *dst = ((row & 0x03) << 6) | ((row & 0x0C) << 2) | ((row & 0x30) >> 2) | (row >> 6);
+        }
+
+        bc1(&dxt1, &mut sub_rgba);
+
+        let (x0, y0) = ((i % 2) * 4, (i / 2) * 4);
+        for y in 0..4 {
+            let src = y * 4 * 4;
+            let dst = ((y0 + y) * 8 + x0) * 4;
+
+            rgba[dst..dst + 16].copy_from_slice(&sub_rgba[src..src + 16]);
+        }
+    }
+}
+
+/// Decodes a palette of big-endian 16-bit entries.
+pub fn palette(data: &[u8], format: GxPaletteFormat) -> Vec<[u8; 4]> {
+    data.chunks_exact(2)
+        .map(|entry| color(u16::from_be_bytes([entry[0], entry[1]]), format))
+        .collect()
+}
+
+fn color(value: u16, format: GxPaletteFormat) -> [u8; 4] {
+    match format {
+        GxPaletteFormat::IA8 => {
+            let [a, v] = value.to_be_bytes();
+            [v, v, v, a]
+        }
+        GxPaletteFormat::RGB565 => {
+            let r = ((value >> 11) & 0x1F) as u8;
+            let g = ((value >> 5) & 0x3F) as u8;
+            let b = (value & 0x1F) as u8;
+
+            [extend5(r), (g << 2) | (g >> 4), extend5(b), 255]
+        }
+        GxPaletteFormat::RGB5A3 => rgb5a3(value),
+    }
+}
+
+/// The top bit selects between opaque RGB555 and ARGB3444.
+fn rgb5a3(value: u16) -> [u8; 4] {
+    if value & 0x8000 != 0 {
+        let r = ((value >> 10) & 0x1F) as u8;
+        let g = ((value >> 5) & 0x1F) as u8;
+        let b = (value & 0x1F) as u8;
+
+        [extend5(r), extend5(g), extend5(b), 255]
+    } else {
+        let a = ((value >> 12) & 0x7) as u8;
+        let r = ((value >> 8) & 0xF) as u8;
+        let g = ((value >> 4) & 0xF) as u8;
+        let b = (value & 0xF) as u8;
+
+        [r * 17, g * 17, b * 17, (a << 5) | (a << 2) | (a >> 1)]
+    }
+}
+
+/// Splits a byte into its nibbles, in `bit_order`. GX stores the high one first.
+fn nibbles(byte: u8, bit_order: BitOrder) -> [u8; 2] {
+    match bit_order {
+        BitOrder::LsbFirst => [byte & 0xF, byte >> 4],
+        BitOrder::MsbFirst => [byte >> 4, byte & 0xF],
+    }
+}
+
+fn extend5(value: u8) -> u8 {
+    (value << 3) | (value >> 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks a block against pixels worked out by hand from the GX texture
+    /// format rules.
+    fn check(decode: impl Fn(&[u8], &mut [u8]), block: &[u8], expected: &[u8]) {
+        let mut rgba = vec![0; expected.len()];
+        decode(block, &mut rgba);
+
+        assert_eq!(rgba, expected);
+    }
+
+    /// A palette fading from green to red.
+    fn ramp() -> Vec<[u8; 4]> {
+        (0..16).map(|i| [i * 17, 255 - i * 17, 0, 255]).collect()
+    }
+
+    /// The left pixel of each pair is in the high nibble.
+    #[rustfmt::skip]
+    #[test]
+    fn i4_msb_first() {
+        check(
+            |block, rgba| i4(block, rgba, BitOrder::MsbFirst),
+            &[
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+            ],
+            &[
+                  0,   0,   0,   0,  17,  17,  17,  17,  34,  34,  34,  34,  51,  51,  51,  51,
+                 68,  68,  68,  68,  85,  85,  85,  85, 102, 102, 102, 102, 119, 119, 119, 119,
+                136, 136, 136, 136, 153, 153, 153, 153, 170, 170, 170, 170, 187, 187, 187, 187,
+                204, 204, 204, 204, 221, 221, 221, 221, 238, 238, 238, 238, 255, 255, 255, 255,
+                  0,   0,   0,   0,  17,  17,  17,  17,  34,  34,  34,  34,  51,  51,  51,  51,
+                 68,  68,  68,  68,  85,  85,  85,  85, 102, 102, 102, 102, 119, 119, 119, 119,
+                136, 136, 136, 136, 153, 153, 153, 153, 170, 170, 170, 170, 187, 187, 187, 187,
+                204, 204, 204, 204, 221, 221, 221, 221, 238, 238, 238, 238, 255, 255, 255, 255,
+                  0,   0,   0,   0,  17,  17,  17,  17,  34,  34,  34,  34,  51,  51,  51,  51,
+                 68,  68,  68,  68,  85,  85,  85,  85, 102, 102, 102, 102, 119, 119, 119, 119,
+                136, 136, 136, 136, 153, 153, 153, 153, 170, 170, 170, 170, 187, 187, 187, 187,
+                204, 204, 204, 204, 221, 221, 221, 221, 238, 238, 238, 238, 255, 255, 255, 255,
+                  0,   0,   0,   0,  17,  17,  17,  17,  34,  34,  34,  34,  51,  51,  51,  51,
+                 68,  68,  68,  68,  85,  85,  85,  85, 102, 102, 102, 102, 119, 119, 119, 119,
+                136, 136, 136, 136, 153, 153, 153, 153, 170, 170, 170, 170, 187, 187, 187, 187,
+                204, 204, 204, 204, 221, 221, 221, 221, 238, 238, 238, 238, 255, 255, 255, 255,
+            ],
+        );
+    }
+
+    /// The left pixel of each pair is in the low nibble.
+    #[rustfmt::skip]
+    #[test]
+    fn i4_lsb_first() {
+        check(
+            |block, rgba| i4(block, rgba, BitOrder::LsbFirst),
+            &[
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+            ],
+            &[
+                 17,  17,  17,  17,   0,   0,   0,   0,  51,  51,  51,  51,  34,  34,  34,  34,
+                 85,  85,  85,  85,  68,  68,  68,  68, 119, 119, 119, 119, 102, 102, 102, 102,
+                153, 153, 153, 153, 136, 136, 136, 136, 187, 187, 187, 187, 170, 170, 170, 170,
+                221, 221, 221, 221, 204, 204, 204, 204, 255, 255, 255, 255, 238, 238, 238, 238,
+                 17,  17,  17,  17,   0,   0,   0,   0,  51,  51,  51,  51,  34,  34,  34,  34,
+                 85,  85,  85,  85,  68,  68,  68,  68, 119, 119, 119, 119, 102, 102, 102, 102,
+                153, 153, 153, 153, 136, 136, 136, 136, 187, 187, 187, 187, 170, 170, 170, 170,
+                221, 221, 221, 221, 204, 204, 204, 204, 255, 255, 255, 255, 238, 238, 238, 238,
+                 17,  17,  17,  17,   0,   0,   0,   0,  51,  51,  51,  51,  34,  34,  34,  34,
+                 85,  85,  85,  85,  68,  68,  68,  68, 119, 119, 119, 119, 102, 102, 102, 102,
+                153, 153, 153, 153, 136, 136, 136, 136, 187, 187, 187, 187, 170, 170, 170, 170,
+                221, 221, 221, 221, 204, 204, 204, 204, 255, 255, 255, 255, 238, 238, 238, 238,
+                 17,  17,  17,  17,   0,   0,   0,   0,  51,  51,  51,  51,  34,  34,  34,  34,
+                 85,  85,  85,  85,  68,  68,  68,  68, 119, 119, 119, 119, 102, 102, 102, 102,
+                153, 153, 153, 153, 136, 136, 136, 136, 187, 187, 187, 187, 170, 170, 170, 170,
+                221, 221, 221, 221, 204, 204, 204, 204, 255, 255, 255, 255, 238, 238, 238, 238,
+            ],
+        );
+    }
+
+    /// Intensity is copied to every channel.
+    #[rustfmt::skip]
+    #[test]
+    fn i8_block() {
+        check(
+            i8,
+            &[
+                0x00, 0x08, 0x10, 0x18, 0x20, 0x28, 0x30, 0x38, 0x40, 0x48, 0x50, 0x58, 0x60, 0x68, 0x70, 0x78,
+                0x80, 0x88, 0x90, 0x98, 0xA0, 0xA8, 0xB0, 0xB8, 0xC0, 0xC8, 0xD0, 0xD8, 0xE0, 0xE8, 0xF0, 0xF8,
+            ],
+            &[
+                  0,   0,   0,   0,   8,   8,   8,   8,  16,  16,  16,  16,  24,  24,  24,  24,
+                 32,  32,  32,  32,  40,  40,  40,  40,  48,  48,  48,  48,  56,  56,  56,  56,
+                 64,  64,  64,  64,  72,  72,  72,  72,  80,  80,  80,  80,  88,  88,  88,  88,
+                 96,  96,  96,  96, 104, 104, 104, 104, 112, 112, 112, 112, 120, 120, 120, 120,
+                128, 128, 128, 128, 136, 136, 136, 136, 144, 144, 144, 144, 152, 152, 152, 152,
+                160, 160, 160, 160, 168, 168, 168, 168, 176, 176, 176, 176, 184, 184, 184, 184,
+                192, 192, 192, 192, 200, 200, 200, 200, 208, 208, 208, 208, 216, 216, 216, 216,
+                224, 224, 224, 224, 232, 232, 232, 232, 240, 240, 240, 240, 248, 248, 248, 248,
+            ],
+        );
+    }
+
+    /// Alpha is the high nibble and intensity the low one.
+    #[rustfmt::skip]
+    #[test]
+    fn ia4_block() {
+        check(
+            ia4,
+            &[
+                0xF0, 0x0F, 0x3C, 0xC3, 0xF0, 0x0F, 0x3C, 0xC3, 0xF0, 0x0F, 0x3C, 0xC3, 0xF0, 0x0F, 0x3C, 0xC3,
+                0xF0, 0x0F, 0x3C, 0xC3, 0xF0, 0x0F, 0x3C, 0xC3, 0xF0, 0x0F, 0x3C, 0xC3, 0xF0, 0x0F, 0x3C, 0xC3,
+            ],
+            &[
+                  0,   0,   0, 255, 255, 255, 255,   0, 204, 204, 204,  51,  51,  51,  51, 204,
+                  0,   0,   0, 255, 255, 255, 255,   0, 204, 204, 204,  51,  51,  51,  51, 204,
+                  0,   0,   0, 255, 255, 255, 255,   0, 204, 204, 204,  51,  51,  51,  51, 204,
+                  0,   0,   0, 255, 255, 255, 255,   0, 204, 204, 204,  51,  51,  51,  51, 204,
+                  0,   0,   0, 255, 255, 255, 255,   0, 204, 204, 204,  51,  51,  51,  51, 204,
+                  0,   0,   0, 255, 255, 255, 255,   0, 204, 204, 204,  51,  51,  51,  51, 204,
+                  0,   0,   0, 255, 255, 255, 255,   0, 204, 204, 204,  51,  51,  51,  51, 204,
+                  0,   0,   0, 255, 255, 255, 255,   0, 204, 204, 204,  51,  51,  51,  51, 204,
+            ],
+        );
+    }
+
+    /// Alpha is the first byte and intensity the second.
+    #[rustfmt::skip]
+    #[test]
+    fn ia8_block() {
+        check(
+            |block, rgba| color16(block, rgba, GxPaletteFormat::IA8),
+            &[
+                0xFF, 0x80, 0x80, 0x00, 0x00, 0xFF, 0x40, 0xC0, 0xFF, 0x80, 0x80, 0x00, 0x00, 0xFF, 0x40, 0xC0,
+                0xFF, 0x80, 0x80, 0x00, 0x00, 0xFF, 0x40, 0xC0, 0xFF, 0x80, 0x80, 0x00, 0x00, 0xFF, 0x40, 0xC0,
+            ],
+            &[
+                128, 128, 128, 255,   0,   0,   0, 128, 255, 255, 255,   0, 192, 192, 192,  64,
+                128, 128, 128, 255,   0,   0,   0, 128, 255, 255, 255,   0, 192, 192, 192,  64,
+                128, 128, 128, 255,   0,   0,   0, 128, 255, 255, 255,   0, 192, 192, 192,  64,
+                128, 128, 128, 255,   0,   0,   0, 128, 255, 255, 255,   0, 192, 192, 192,  64,
+            ],
+        );
+    }
+
+    /// Pixels are big-endian.
+    #[rustfmt::skip]
+    #[test]
+    fn rgb565_block() {
+        check(
+            |block, rgba| color16(block, rgba, GxPaletteFormat::RGB565),
+            &[
+                0xF8, 0x00, 0x07, 0xE0, 0x00, 0x1F, 0x84, 0x10, 0xF8, 0x00, 0x07, 0xE0, 0x00, 0x1F, 0x84, 0x10,
+                0xF8, 0x00, 0x07, 0xE0, 0x00, 0x1F, 0x84, 0x10, 0xF8, 0x00, 0x07, 0xE0, 0x00, 0x1F, 0x84, 0x10,
+            ],
+            &[
+                255,   0,   0, 255,   0, 255,   0, 255,   0,   0, 255, 255, 132, 130, 132, 255,
+                255,   0,   0, 255,   0, 255,   0, 255,   0,   0, 255, 255, 132, 130, 132, 255,
+                255,   0,   0, 255,   0, 255,   0, 255,   0,   0, 255, 255, 132, 130, 132, 255,
+                255,   0,   0, 255,   0, 255,   0, 255,   0,   0, 255, 255, 132, 130, 132, 255,
+            ],
+        );
+    }
+
+    /// The top bit selects opaque RGB555 for the first two pixels and ARGB3444 for
+    /// the last two.
+    #[rustfmt::skip]
+    #[test]
+    fn rgb5a3_block() {
+        check(
+            |block, rgba| color16(block, rgba, GxPaletteFormat::RGB5A3),
+            &[
+                0xFC, 0x00, 0x82, 0x00, 0x7F, 0x84, 0x31, 0x23, 0xFC, 0x00, 0x82, 0x00, 0x7F, 0x84, 0x31, 0x23,
+                0xFC, 0x00, 0x82, 0x00, 0x7F, 0x84, 0x31, 0x23, 0xFC, 0x00, 0x82, 0x00, 0x7F, 0x84, 0x31, 0x23,
+            ],
+            &[
+                255,   0,   0, 255,   0, 132,   0, 255, 255, 136,  68, 255,  17,  34,  51, 109,
+                255,   0,   0, 255,   0, 132,   0, 255, 255, 136,  68, 255,  17,  34,  51, 109,
+                255,   0,   0, 255,   0, 132,   0, 255, 255, 136,  68, 255,  17,  34,  51, 109,
+                255,   0,   0, 255,   0, 132,   0, 255, 255, 136,  68, 255,  17,  34,  51, 109,
+            ],
+        );
+    }
+
+    /// The AR pairs of all pixels come before the GB pairs.
+    #[rustfmt::skip]
+    #[test]
+    fn rgba32_block() {
+        check(
+            rgba32,
+            &[
+                0xFF, 0x00, 0xFE, 0x10, 0xFD, 0x20, 0xFC, 0x30, 0xFB, 0x40, 0xFA, 0x50, 0xF9, 0x60, 0xF8, 0x70,
+                0xF7, 0x80, 0xF6, 0x90, 0xF5, 0xA0, 0xF4, 0xB0, 0xF3, 0xC0, 0xF2, 0xD0, 0xF1, 0xE0, 0xF0, 0xF0,
+                0xFF, 0x00, 0xEF, 0x01, 0xDF, 0x02, 0xCF, 0x03, 0xBF, 0x04, 0xAF, 0x05, 0x9F, 0x06, 0x8F, 0x07,
+                0x7F, 0x08, 0x6F, 0x09, 0x5F, 0x0A, 0x4F, 0x0B, 0x3F, 0x0C, 0x2F, 0x0D, 0x1F, 0x0E, 0x0F, 0x0F,
+            ],
+            &[
+                  0, 255,   0, 255,  16, 239,   1, 254,  32, 223,   2, 253,  48, 207,   3, 252,
+                 64, 191,   4, 251,  80, 175,   5, 250,  96, 159,   6, 249, 112, 143,   7, 248,
+                128, 127,   8, 247, 144, 111,   9, 246, 160,  95,  10, 245, 176,  79,  11, 244,
+                192,  63,  12, 243, 208,  47,  13, 242, 224,  31,  14, 241, 240,  15,  15, 240,
+            ],
+        );
+    }
+
+    /// Indices count up through the palette, high nibble first.
+    #[rustfmt::skip]
+    #[test]
+    fn c4_block() {
+        check(
+            |block, rgba| c4(block, rgba, &ramp(), BitOrder::MsbFirst),
+            &[
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+                0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF,
+            ],
+            &[
+                  0, 255,   0, 255,  17, 238,   0, 255,  34, 221,   0, 255,  51, 204,   0, 255,
+                 68, 187,   0, 255,  85, 170,   0, 255, 102, 153,   0, 255, 119, 136,   0, 255,
+                136, 119,   0, 255, 153, 102,   0, 255, 170,  85,   0, 255, 187,  68,   0, 255,
+                204,  51,   0, 255, 221,  34,   0, 255, 238,  17,   0, 255, 255,   0,   0, 255,
+                  0, 255,   0, 255,  17, 238,   0, 255,  34, 221,   0, 255,  51, 204,   0, 255,
+                 68, 187,   0, 255,  85, 170,   0, 255, 102, 153,   0, 255, 119, 136,   0, 255,
+                136, 119,   0, 255, 153, 102,   0, 255, 170,  85,   0, 255, 187,  68,   0, 255,
+                204,  51,   0, 255, 221,  34,   0, 255, 238,  17,   0, 255, 255,   0,   0, 255,
+                  0, 255,   0, 255,  17, 238,   0, 255,  34, 221,   0, 255,  51, 204,   0, 255,
+                 68, 187,   0, 255,  85, 170,   0, 255, 102, 153,   0, 255, 119, 136,   0, 255,
+                136, 119,   0, 255, 153, 102,   0, 255, 170,  85,   0, 255, 187,  68,   0, 255,
+                204,  51,   0, 255, 221,  34,   0, 255, 238,  17,   0, 255, 255,   0,   0, 255,
+                  0, 255,   0, 255,  17, 238,   0, 255,  34, 221,   0, 255,  51, 204,   0, 255,
+                 68, 187,   0, 255,  85, 170,   0, 255, 102, 153,   0, 255, 119, 136,   0, 255,
+                136, 119,   0, 255, 153, 102,   0, 255, 170,  85,   0, 255, 187,  68,   0, 255,
+                204,  51,   0, 255, 221,  34,   0, 255, 238,  17,   0, 255, 255,   0,   0, 255,
+            ],
+        );
+    }
+
+    /// Indices count down through the palette.
+    #[rustfmt::skip]
+    #[test]
+    fn c8_block() {
+        check(
+            |block, rgba| c8(block, rgba, &ramp()),
+            &[
+                0x0F, 0x0E, 0x0D, 0x0C, 0x0B, 0x0A, 0x09, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0x00,
+                0x0F, 0x0E, 0x0D, 0x0C, 0x0B, 0x0A, 0x09, 0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, 0x00,
+            ],
+            &[
+                255,   0,   0, 255, 238,  17,   0, 255, 221,  34,   0, 255, 204,  51,   0, 255,
+                187,  68,   0, 255, 170,  85,   0, 255, 153, 102,   0, 255, 136, 119,   0, 255,
+                119, 136,   0, 255, 102, 153,   0, 255,  85, 170,   0, 255,  68, 187,   0, 255,
+                 51, 204,   0, 255,  34, 221,   0, 255,  17, 238,   0, 255,   0, 255,   0, 255,
+                255,   0,   0, 255, 238,  17,   0, 255, 221,  34,   0, 255, 204,  51,   0, 255,
+                187,  68,   0, 255, 170,  85,   0, 255, 153, 102,   0, 255, 136, 119,   0, 255,
+                119, 136,   0, 255, 102, 153,   0, 255,  85, 170,   0, 255,  68, 187,   0, 255,
+                 51, 204,   0, 255,  34, 221,   0, 255,  17, 238,   0, 255,   0, 255,   0, 255,
+            ],
+        );
+    }
+
+    /// The top two bits of each big-endian index are ignored.
+    #[rustfmt::skip]
+    #[test]
+    fn c14x2_block() {
+        check(
+            |block, rgba| c14x2(block, rgba, &ramp()),
+            &[
+                0xC0, 0x00, 0xC0, 0x01, 0xC0, 0x02, 0xC0, 0x03, 0xC0, 0x04, 0xC0, 0x05, 0xC0, 0x06, 0xC0, 0x07,
+                0xC0, 0x08, 0xC0, 0x09, 0xC0, 0x0A, 0xC0, 0x0B, 0xC0, 0x0C, 0xC0, 0x0D, 0xC0, 0x0E, 0xC0, 0x0F,
+            ],
+            &[
+                  0, 255,   0, 255,  17, 238,   0, 255,  34, 221,   0, 255,  51, 204,   0, 255,
+                 68, 187,   0, 255,  85, 170,   0, 255, 102, 153,   0, 255, 119, 136,   0, 255,
+                136, 119,   0, 255, 153, 102,   0, 255, 170,  85,   0, 255, 187,  68,   0, 255,
+                204,  51,   0, 255, 221,  34,   0, 255, 238,  17,   0, 255, 255,   0,   0, 255,
+            ],
+        );
+    }
+
+    /// Each 4x4 quadrant is a big-endian DXT1 block with its leftmost pixel in the
+    /// high bits. The last one uses punch-through alpha.
+    #[rustfmt::skip]
+    #[test]
+    fn cmpr_block() {
+        check(
+            cmpr,
+            &[
+                0xF8, 0x00, 0x00, 0x1F, 0x05, 0x05, 0x05, 0x05, 0x07, 0xE0, 0x00, 0x00, 0x50, 0x50, 0x50, 0x50,
+                0xFF, 0xFF, 0x00, 0x00, 0x00, 0x55, 0x00, 0x55, 0x00, 0x00, 0xFF, 0xFF, 0xCC, 0xCC, 0xCC, 0xCC,
+            ],
+            &[
+                255,   0,   0, 255, 255,   0,   0, 255,   0,   0, 255, 255,   0,   0, 255, 255,
+                  0,   0,   0, 255,   0,   0,   0, 255,   0, 255,   0, 255,   0, 255,   0, 255,
+                255,   0,   0, 255, 255,   0,   0, 255,   0,   0, 255, 255,   0,   0, 255, 255,
+                  0,   0,   0, 255,   0,   0,   0, 255,   0, 255,   0, 255,   0, 255,   0, 255,
+                255,   0,   0, 255, 255,   0,   0, 255,   0,   0, 255, 255,   0,   0, 255, 255,
+                  0,   0,   0, 255,   0,   0,   0, 255,   0, 255,   0, 255,   0, 255,   0, 255,
+                255,   0,   0, 255, 255,   0,   0, 255,   0,   0, 255, 255,   0,   0, 255, 255,
+                  0,   0,   0, 255,   0,   0,   0, 255,   0, 255,   0, 255,   0, 255,   0, 255,
+                255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
+                  0,   0,   0,   0,   0,   0,   0, 255,   0,   0,   0,   0,   0,   0,   0, 255,
+                  0,   0,   0, 255,   0,   0,   0, 255,   0,   0,   0, 255,   0,   0,   0, 255,
+                  0,   0,   0,   0,   0,   0,   0, 255,   0,   0,   0,   0,   0,   0,   0, 255,
+                255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 255,
+                  0,   0,   0,   0,   0,   0,   0, 255,   0,   0,   0,   0,   0,   0,   0, 255,
+                  0,   0,   0, 255,   0,   0,   0, 255,   0,   0,   0, 255,   0,   0,   0, 255,
+                  0,   0,   0,   0,   0,   0,   0, 255,   0,   0,   0,   0,   0,   0,   0, 255,
+            ],
+        );
+    }
+}
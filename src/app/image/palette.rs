@@ -0,0 +1,179 @@
+use crate::app::image_format::PaletteFile;
+
+/// Parses a palette file into RGBA colors.
+pub fn parse(data: &[u8], format: PaletteFile) -> Result<Vec<u8>, String> {
+    let rgba = match format {
+        PaletteFile::Raw => unreachable!(),
+        PaletteFile::JascPal => jasc_pal(data)?,
+        PaletteFile::Act => act(data)?,
+        PaletteFile::Gpl => gpl(data)?,
+        PaletteFile::Png => png(data)?,
+    };
+
+    if rgba.is_empty() {
+        return Err("palette file has no colors".into());
+    }
+
+    Ok(rgba)
+}
+
+/// Paint Shop Pro palette: a `JASC-PAL` header, a version, the color count and
+/// one `r g b` line per color.
+fn jasc_pal(data: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(data).map_err(|_| "JASC-PAL file is not text")?;
+    let mut lines = text.lines().map(str::trim);
+
+    if lines.next() != Some("JASC-PAL") {
+        return Err("missing JASC-PAL header".into());
+    }
+
+    let _version = lines.next();
+    let count: usize = lines
+        .next()
+        .and_then(|line| line.parse().ok())
+        .ok_or("invalid JASC-PAL color count")?;
+
+    let mut rgba = Vec::with_capacity(count * 4);
+    for line in lines.filter(|line| !line.is_empty()).take(count) {
+        rgba.extend(parse_rgb(line).ok_or("invalid JASC-PAL color")?);
+        rgba.push(255);
+    }
+
+    Ok(rgba)
+}
+
+/// Adobe Color Table: 256 RGB colors, optionally followed by a big-endian color
+/// count and transparent color index.
+fn act(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 256 * 3 {
+        return Err("ACT file is too short".into());
+    }
+
+    let (count, transparent) = match data.get(768..772) {
+        Some(footer) => (
+            u16::from_be_bytes([footer[0], footer[1]]) as usize,
+            u16::from_be_bytes([footer[2], footer[3]]) as usize,
+        ),
+        None => (256, usize::MAX),
+    };
+    let count = if count == 0 || count > 256 {
+        256
+    } else {
+        count
+    };
+
+    let mut rgba = Vec::with_capacity(count * 4);
+    for (i, rgb) in data[..count * 3].chunks_exact(3).enumerate() {
+        rgba.extend_from_slice(rgb);
+        rgba.push(if i == transparent { 0 } else { 255 });
+    }
+
+    Ok(rgba)
+}
+
+/// GIMP palette: a `GIMP Palette` header, optional `Name:` and `Columns:` lines,
+/// `#` comments and one `r g b [name]` line per color.
+fn gpl(data: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(data).map_err(|_| "GPL file is not text")?;
+    let mut lines = text.lines().map(str::trim);
+
+    if lines.next() != Some("GIMP Palette") {
+        return Err("missing GIMP Palette header".into());
+    }
+
+    let mut rgba = Vec::new();
+    for line in lines {
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("Name:")
+            || line.starts_with("Columns:")
+        {
+            continue;
+        }
+
+        rgba.extend(parse_rgb(line).ok_or("invalid GPL color")?);
+        rgba.push(255);
+    }
+
+    Ok(rgba)
+}
+
+/// Palette of an indexed PNG, with alpha from its tRNS chunk.
+fn png(data: &[u8]) -> Result<Vec<u8>, String> {
+    let reader = png::Decoder::new(data)
+        .read_info()
+        .map_err(|err| err.to_string())?;
+    let info = reader.info();
+
+    let plte = info.palette.as_deref().ok_or("PNG file has no palette")?;
+    let trns = info.trns.as_deref().unwrap_or_default();
+
+    let mut rgba = Vec::with_capacity(plte.len() / 3 * 4);
+    for (i, rgb) in plte.chunks_exact(3).enumerate() {
+        rgba.extend_from_slice(rgb);
+        rgba.push(trns.get(i).copied().unwrap_or(255));
+    }
+
+    Ok(rgba)
+}
+
+/// Writes RGBA colors as a JASC-PAL file.
+pub fn write_jasc_pal(rgba: &[u8]) -> Vec<u8> {
+    let mut text = format!("JASC-PAL\r\n0100\r\n{}\r\n", rgba.len() / 4);
+    for color in rgba.chunks_exact(4) {
+        text += &format!("{} {} {}\r\n", color[0], color[1], color[2]);
+    }
+
+    text.into_bytes()
+}
+
+/// Writes up to 256 RGBA colors as an ACT file. The footer stores the color
+/// count and the first fully transparent color, if any.
+pub fn write_act(rgba: &[u8]) -> Vec<u8> {
+    let colors: Vec<_> = rgba.chunks_exact(4).take(256).collect();
+    let transparent = colors
+        .iter()
+        .position(|color| color[3] == 0)
+        .map_or(0xFFFF, |i| i as u16);
+
+    let mut data = vec![0; 772];
+    for (dst, color) in data.chunks_exact_mut(3).zip(&colors) {
+        dst.copy_from_slice(&color[..3]);
+    }
+    data[768..770].copy_from_slice(&(colors.len() as u16).to_be_bytes());
+    data[770..772].copy_from_slice(&transparent.to_be_bytes());
+
+    data
+}
+
+/// Writes RGBA colors as a GIMP palette, 16 colors per row.
+pub fn write_gpl(rgba: &[u8]) -> Vec<u8> {
+    let mut text = String::from("GIMP Palette\nName: Palette\nColumns: 16\n#\n");
+    for (i, color) in rgba.chunks_exact(4).enumerate() {
+        text += &format!("{:3} {:3} {:3}\tIndex {i}\n", color[0], color[1], color[2]);
+    }
+
+    text.into_bytes()
+}
+
+/// Lays out RGBA colors as a 16 pixel wide image, one pixel per color. Returns
+/// its size and pixels, with unused pixels left transparent.
+pub fn swatch(rgba: &[u8]) -> (usize, usize, Vec<u8>) {
+    let (w, h) = (16, (rgba.len() / 4).div_ceil(16));
+
+    let mut pixels = vec![0; w * h * 4];
+    pixels[..rgba.len()].copy_from_slice(rgba);
+
+    (w, h, pixels)
+}
+
+/// Parses the first three whitespace separated values of a line.
+fn parse_rgb(line: &str) -> Option<[u8; 3]> {
+    let mut values = line.split_whitespace().map(str::parse);
+
+    Some([
+        values.next()?.ok()?,
+        values.next()?.ok()?,
+        values.next()?.ok()?,
+    ])
+}
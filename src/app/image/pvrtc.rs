@@ -0,0 +1,233 @@
+/// Color A and B of a PVRTC block, with 5-bit RGB and 4-bit alpha.
+struct Block {
+    a: [i32; 4],
+    b: [i32; 4],
+    modulation: u32,
+    mode: bool,
+}
+
+impl Block {
+    /// Splits an 8-byte block into its colors and modulation data.
+    fn new(block: &[u8]) -> Self {
+        let modulation = u32::from_le_bytes(block[..4].try_into().unwrap());
+        let color = u32::from_le_bytes(block[4..8].try_into().unwrap());
+
+        let opaque_a = (color >> 15) & 1 == 1;
+        let opaque_b = color >> 31 == 1;
+        let field = |shift: u32, count: u32| ((color >> shift) & ((1 << count) - 1)) as i32;
+
+        let a = if opaque_a {
+            [field(10, 5), field(5, 5), extend(field(1, 4), 4), 15]
+        } else {
+            [
+                extend(field(8, 4), 4),
+                extend(field(4, 4), 4),
+                extend(field(1, 3), 3),
+                field(12, 3) << 1,
+            ]
+        };
+        let b = if opaque_b {
+            [field(26, 5), field(21, 5), field(16, 5), 15]
+        } else {
+            [
+                extend(field(24, 4), 4),
+                extend(field(20, 4), 4),
+                extend(field(16, 4), 4),
+                field(28, 3) << 1,
+            ]
+        };
+
+        Self {
+            a,
+            b,
+            modulation,
+            mode: color & 1 == 1,
+        }
+    }
+}
+
+/// Number of blocks stored for a `w` x `h` PVRTC1 image. PVRTC1 only supports
+/// power-of-two sizes, so both dimensions are rounded up to a power of two of at
+/// least two blocks.
+pub fn block_count(w: usize, h: usize, block_w: usize, block_h: usize) -> (usize, usize) {
+    (
+        w.div_ceil(block_w).next_power_of_two().max(2),
+        h.div_ceil(block_h).next_power_of_two().max(2),
+    )
+}
+
+/// Decodes a `w` x `h` PVRTC1 image stored as `blocks_x` x `blocks_y` blocks in
+/// Morton order. Colors A and B of each pixel are bilinearly blended from the four
+/// nearest block centers, wrapping around the edges, so blocks can't be decoded on
+/// their own.
+pub fn pvrtc(
+    data: &[u8],
+    w: usize,
+    h: usize,
+    (blocks_x, blocks_y): (usize, usize),
+    bpp2: bool,
+) -> Vec<u8> {
+    let (block_w, block_h) = if bpp2 { (8, 4) } else { (4, 4) };
+    let (full_w, full_h) = (blocks_x * block_w, blocks_y * block_h);
+
+    let mut blocks = Vec::with_capacity(blocks_x * blocks_y);
+    for y in 0..blocks_y {
+        for x in 0..blocks_x {
+            let i = morton(x, y, blocks_x, blocks_y) * 8;
+            blocks.push(Block::new(&data[i..i + 8]));
+        }
+    }
+
+    let modulation = Modulation::new(&blocks, blocks_x, full_w, full_h, bpp2);
+    let total = (block_w * block_h) as i32;
+    let mut rgba = vec![0; w * h * 4];
+
+    for py in 0..h {
+        for px in 0..w {
+            let x = px + full_w - block_w / 2;
+            let y = py + full_h - block_h / 2;
+            let (bx0, fx) = ((x / block_w) % blocks_x, (x % block_w) as i32);
+            let (by0, fy) = ((y / block_h) % blocks_y, (y % block_h) as i32);
+            let (bx1, by1) = ((bx0 + 1) % blocks_x, (by0 + 1) % blocks_y);
+
+            let blend = |color: fn(&Block) -> [i32; 4]| -> [i32; 4] {
+                let p = color(&blocks[by0 * blocks_x + bx0]);
+                let q = color(&blocks[by0 * blocks_x + bx1]);
+                let r = color(&blocks[by1 * blocks_x + bx0]);
+                let s = color(&blocks[by1 * blocks_x + bx1]);
+                let (iw, ih) = (block_w as i32 - fx, block_h as i32 - fy);
+
+                std::array::from_fn(|c| {
+                    p[c] * iw * ih + q[c] * fx * ih + r[c] * iw * fy + s[c] * fx * fy
+                })
+            };
+            let a = blend(|block| block.a);
+            let b = blend(|block| block.b);
+
+            let (weight, punch_through) = modulation.weight(px, py);
+            let dst = (py * w + px) * 4;
+
+            for c in 0..4 {
+                let max = if c == 3 { 15 } else { 31 } * total * 8;
+                let value = b[c] * weight + a[c] * (8 - weight);
+
+                rgba[dst + c] = ((value * 255 + max / 2) / max) as u8;
+            }
+            if punch_through {
+                rgba[dst + 3] = 0;
+            }
+        }
+    }
+
+    rgba
+}
+
+/// Per-pixel modulation values of the whole image, needed because 2bpp blocks
+/// can interpolate missing values from pixels of their neighbors.
+struct Modulation {
+    values: Vec<u8>,
+    modes: Vec<u8>,
+    w: usize,
+    h: usize,
+    bpp2: bool,
+}
+
+impl Modulation {
+    fn new(blocks: &[Block], blocks_x: usize, w: usize, h: usize, bpp2: bool) -> Self {
+        let (block_w, block_h) = if bpp2 { (8, 4) } else { (4, 4) };
+        let mut values = vec![0; w * h];
+        let mut modes = vec![0; w * h];
+
+        for (i, block) in blocks.iter().enumerate() {
+            let (x0, y0) = ((i % blocks_x) * block_w, (i / blocks_x) * block_h);
+            let mut bits = block.modulation;
+            let mut mode = block.mode as u8;
+
+            if bpp2 && mode == 1 {
+                // The low bit of the first and center values selects between
+                // horizontal and vertical interpolation, leaving one bit for each.
+                if bits & 1 == 1 {
+                    mode = if (bits >> 20) & 1 == 1 { 3 } else { 2 };
+                    bits = (bits & !(1 << 20)) | (((bits >> 21) & 1) << 20);
+                }
+                bits = (bits & !1) | ((bits >> 1) & 1);
+            }
+
+            for y in 0..block_h {
+                for x in 0..block_w {
+                    let dst = (y0 + y) * w + x0 + x;
+                    modes[dst] = mode;
+
+                    if !bpp2 || mode != 0 {
+                        if !bpp2 || (x ^ y) & 1 == 0 {
+                            values[dst] = (bits & 3) as u8;
+                            bits >>= 2;
+                        }
+                    } else {
+                        values[dst] = (bits & 1) as u8 * 3;
+                        bits >>= 1;
+                    }
+                }
+            }
+        }
+
+        Self {
+            values,
+            modes,
+            w,
+            h,
+            bpp2,
+        }
+    }
+
+    /// Weight of color B out of 8 for the pixel at `(x, y)`, and whether the pixel
+    /// is punched through.
+    fn weight(&self, x: usize, y: usize) -> (i32, bool) {
+        const WEIGHTS: [i32; 4] = [0, 3, 5, 8];
+
+        let at =
+            |x: usize, y: usize| WEIGHTS[self.values[(y % self.h) * self.w + x % self.w] as usize];
+        let i = y * self.w + x;
+        let value = self.values[i] as usize;
+
+        match (self.bpp2, self.modes[i]) {
+            (false, 0) => (WEIGHTS[value], false),
+            (false, _) => ([0, 4, 4, 8][value], value == 2),
+            (true, 0) => (WEIGHTS[value], false),
+            (true, _) if (x ^ y) & 1 == 0 => (WEIGHTS[value], false),
+            (true, mode) => {
+                let (left, right) = (at(x + self.w - 1, y), at(x + 1, y));
+                let (up, down) = (at(x, y + self.h - 1), at(x, y + 1));
+
+                let weight = match mode {
+                    1 => (left + right + up + down + 2) / 4,
+                    2 => (left + right + 1) / 2,
+                    _ => (up + down + 1) / 2,
+                };
+                (weight, false)
+            }
+        }
+    }
+}
+
+/// Index of block `(x, y)` in Morton order, with `y` in the low bit of each pair.
+/// Bits of the longer side beyond the shorter one are appended as is.
+fn morton(x: usize, y: usize, w: usize, h: usize) -> usize {
+    let mut index = 0;
+    let mut bit = 0;
+
+    while 1 << bit < w.min(h) {
+        index |= ((y >> bit) & 1) << (2 * bit);
+        index |= ((x >> bit) & 1) << (2 * bit + 1);
+        bit += 1;
+    }
+
+    let rest = if h < w { x } else { y } >> bit;
+
+    index | (rest << (2 * bit))
+}
+
+/// Expands a `bits` wide channel to 5 bits by replicating its high bits.
+fn extend(value: i32, bits: u32) -> i32 {
+    (value << (5 - bits)) | (value >> (2 * bits - 5))
+}
@@ -0,0 +1,595 @@
+use crate::app::image_format::TileOrder;
+use crate::app::swizzle::{Gx2TileMode, Swizzle, SwizzleState};
+
+/// Block numbers of a PSMCT32 and PSMT8 page, 8x4 blocks.
+const PS2_BLOCKS_32: [[usize; 8]; 4] = [
+    [0, 1, 4, 5, 16, 17, 20, 21],
+    [2, 3, 6, 7, 18, 19, 22, 23],
+    [8, 9, 12, 13, 24, 25, 28, 29],
+    [10, 11, 14, 15, 26, 27, 30, 31],
+];
+
+/// Block numbers of a PSMT4 page, 4x8 blocks.
+const PS2_BLOCKS_4: [[usize; 4]; 8] = [
+    [0, 2, 8, 10],
+    [1, 3, 9, 11],
+    [4, 6, 12, 14],
+    [5, 7, 13, 15],
+    [16, 18, 24, 26],
+    [17, 19, 25, 27],
+    [20, 22, 28, 30],
+    [21, 23, 29, 31],
+];
+
+/// Word numbers of the two rows of a PSMCT32 column.
+const PS2_COLUMN_WORDS: [[usize; 8]; 2] =
+    [[0, 1, 4, 5, 8, 9, 12, 13], [2, 3, 6, 7, 10, 11, 14, 15]];
+
+/// Pipes, banks and sizes in bytes of the Wii U memory layout.
+const GX2_PIPES: usize = 2;
+const GX2_BANKS: usize = 4;
+const GX2_GROUP_SIZE: usize = 256;
+const GX2_SWAP_SIZE: usize = 256;
+const GX2_ROW_SIZE: usize = 2048;
+const GX2_SPLIT_SIZE: usize = 2048;
+
+/// Position of each pixel of a `w` x `h` image in the swizzled data, in pixels of
+/// `bits` each, listed in row-major order. Unswizzled data needs no positions.
+pub fn positions(
+    swizzle: &SwizzleState,
+    w: usize,
+    h: usize,
+    bits: usize,
+) -> Result<Option<Vec<usize>>, String> {
+    let position: Box<dyn Fn(usize, usize) -> usize> = match swizzle.selected {
+        Swizzle::None => return Ok(None),
+        Swizzle::Ps2 => match bits {
+            32 => Box::new(|x, y| ps2_psmct32(x, y, w)),
+            8 => Box::new(|x, y| ps2_psmt8(x, y, w)),
+            4 => Box::new(|x, y| ps2_psmt4(x, y, w)),
+            _ => return Err("PS2 swizzle needs 4, 8 or 32 bits per pixel".into()),
+        },
+        Swizzle::Ps2Ct32 => {
+            let page_pixels = ps2_page_pixels();
+            match bits {
+                8 => Box::new(move |x, y| {
+                    let byte = ps2_psmt8(x, y, w);
+                    ps2_ct32_upload(byte / 4, w, &page_pixels) * 4 + byte % 4
+                }),
+                4 => Box::new(move |x, y| {
+                    let nibble = ps2_psmt4(x, y, w);
+                    ps2_ct32_upload(nibble / 8, w, &page_pixels) * 8 + nibble % 8
+                }),
+                _ => return Err("PS2 CT32 swizzle needs 4 or 8 bits per pixel".into()),
+            }
+        }
+        Swizzle::Psp if 128 % bits != 0 => {
+            return Err("PSP swizzle needs 4, 8, 16 or 32 bits per pixel".into());
+        }
+        Swizzle::Psp => Box::new(|x, y| psp(x, y, w, bits)),
+        Swizzle::Vita => {
+            let (w, h) = (w.next_power_of_two(), h.next_power_of_two());
+            Box::new(move |x, y| morton(x, y, w, h))
+        }
+        Swizzle::BlockLinear if 128 % bits != 0 => {
+            return Err("Switch swizzle needs 4, 8, 16, 32, 64 or 128 bits per pixel".into());
+        }
+        Swizzle::BlockLinear => {
+            let block_height = swizzle.block_height(h)?;
+            Box::new(move |x, y| block_linear(x, y, w, bits, block_height))
+        }
+        Swizzle::Xenos | Swizzle::Gx2 if !matches!(bits, 8 | 16 | 32 | 64 | 128) => {
+            return Err(format!(
+                "{} swizzle needs 8, 16, 32, 64 or 128 bits per pixel",
+                swizzle.selected
+            ));
+        }
+        Swizzle::Xenos => return Ok(Some(xenos(w, h, bits))),
+        Swizzle::Gx2 => {
+            let surface = Gx2Surface::new(swizzle.gx2_tile_mode, w, bits, swizzle.gx2_swizzle()?);
+            Box::new(move |x, y| surface.position(x, y))
+        }
+    };
+
+    Ok(Some(
+        (0..h)
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .map(|(x, y)| position(x, y))
+            .collect(),
+    ))
+}
+
+/// Position of each pixel of a `w` x `h` image stored as `tile_w` x `tile_h` tiles,
+/// listed in row-major order. Morton order inside tiles needs power-of-two tiles.
+/// Morton order across tiles rounds the tile counts up to a power of two, leaving
+/// gaps for the missing tiles.
+pub fn tiled(
+    w: usize,
+    h: usize,
+    (tile_w, tile_h): (usize, usize),
+    pixel_order: TileOrder,
+    tile_order: TileOrder,
+) -> Result<Vec<usize>, String> {
+    if pixel_order == TileOrder::Morton && !(tile_w.is_power_of_two() && tile_h.is_power_of_two()) {
+        return Err("Morton order needs power-of-two tile sizes".into());
+    }
+
+    let tiles_x = w / tile_w;
+    let (morton_w, morton_h) = (
+        tiles_x.next_power_of_two(),
+        (h / tile_h).next_power_of_two(),
+    );
+
+    Ok((0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let (tile_x, tile_y) = (x / tile_w, y / tile_h);
+            let (pixel_x, pixel_y) = (x % tile_w, y % tile_h);
+
+            let tile = match tile_order {
+                TileOrder::RowMajor => tile_y * tiles_x + tile_x,
+                TileOrder::Morton => morton(tile_x, tile_y, morton_w, morton_h),
+            };
+            let pixel = match pixel_order {
+                TileOrder::RowMajor => pixel_y * tile_w + pixel_x,
+                TileOrder::Morton => morton(pixel_x, pixel_y, tile_w, tile_h),
+            };
+
+            tile * tile_w * tile_h + pixel
+        })
+        .collect())
+}
+
+/// Word address of a PSMCT32 pixel. Pages are 64x32 pixels of 32 8x8 blocks, and
+/// blocks are 4 columns of 8x2 pixels.
+fn ps2_psmct32(x: usize, y: usize, w: usize) -> usize {
+    let page = (y / 32) * w.div_ceil(64) + x / 64;
+    let block = PS2_BLOCKS_32[(y % 32) / 8][(x % 64) / 8];
+    let column = (y % 8) / 2;
+
+    page * 2048 + block * 64 + column * 16 + PS2_COLUMN_WORDS[y % 2][x % 8]
+}
+
+/// Byte address of a PSMT8 pixel. Pages are 128x64 pixels of 32 16x16 blocks,
+/// and blocks are 4 columns of 16x4 pixels. Each word of a column holds 4 pixels,
+/// with every other pair of rows shifted by 4 words.
+fn ps2_psmt8(x: usize, y: usize, w: usize) -> usize {
+    let page = (y / 64) * w.div_ceil(128) + x / 128;
+    let block = PS2_BLOCKS_32[(y % 64) / 16][(x % 128) / 16];
+    let column = (y % 16) / 4;
+    let swap = ((y + 2) >> 2) & 1;
+    let word = PS2_COLUMN_WORDS[y % 2][(x + swap * 4) % 8];
+    let byte = ((y >> 1) & 1) | ((x >> 2) & 2);
+
+    page * 8192 + block * 256 + column * 64 + word * 4 + byte
+}
+
+/// Nibble address of a PSMT4 pixel. Pages are 128x128 pixels of 32 32x16 blocks,
+/// and blocks are 4 columns of 32x4 pixels laid out like PSMT8 ones, with 8
+/// pixels per word.
+fn ps2_psmt4(x: usize, y: usize, w: usize) -> usize {
+    let page = (y / 128) * w.div_ceil(128) + x / 128;
+    let block = PS2_BLOCKS_4[(y % 128) / 16][(x % 128) / 32];
+    let column = (y % 16) / 4;
+    let swap = ((y + 2) >> 2) & 1;
+    let word = PS2_COLUMN_WORDS[y % 2][(x + swap * 4) % 8];
+    let nibble = ((x >> 3) & 3) * 2 + ((y >> 1) & 1);
+
+    (page * 8192 + block * 256 + column * 64 + word * 4) * 2 + nibble
+}
+
+/// Pixel index in a 64x32 PSMCT32 page of each word of the page.
+fn ps2_page_pixels() -> Vec<usize> {
+    let mut pixels = vec![0; 2048];
+    for y in 0..32 {
+        for x in 0..64 {
+            pixels[ps2_psmct32(x, y, 64)] = y * 64 + x;
+        }
+    }
+
+    pixels
+}
+
+/// Position of the word at GS `address` in a row-major PSMCT32 image, for a
+/// PSMT8 or PSMT4 texture `w` pixels wide uploaded as a PSMCT32 one `w / 2`
+/// pixels wide. Pages span as many pixels of the texture in both formats.
+fn ps2_ct32_upload(address: usize, w: usize, page_pixels: &[usize]) -> usize {
+    let pages_x = w.div_ceil(128);
+    let (page, pixel) = (address / 2048, page_pixels[address % 2048]);
+    let x = (page % pages_x) * 64 + pixel % 64;
+    let y = (page / pages_x) * 32 + pixel / 64;
+
+    y * w.div_ceil(2) + x
+}
+
+/// Position of a PSP pixel. Rows are padded to 16 bytes and split into blocks of
+/// 16 bytes by 8 rows, stored in row-major order.
+fn psp(x: usize, y: usize, w: usize, bits: usize) -> usize {
+    let blocks_x = (w * bits).div_ceil(128);
+    let bit = x * bits;
+    let block = (y / 8) * blocks_x + bit / 128;
+
+    (block * 1024 + (y % 8) * 128 + bit % 128) / bits
+}
+
+/// Position of a pixel in block-linear layout. GOBs of 64 bytes by 8 rows are
+/// stacked `block_height` high into blocks, stored in row-major order. Inside a GOB,
+/// 16x2 byte sectors are arranged in two columns of 4x2 sectors.
+fn block_linear(x: usize, y: usize, w: usize, bits: usize, block_height: usize) -> usize {
+    let gobs_x = (w * bits).div_ceil(512);
+    let block_rows = block_height * 8;
+    let bit = x * bits;
+    let byte = bit / 8;
+
+    let gob = (y / block_rows) * gobs_x * block_height
+        + (byte / 64) * block_height
+        + (y % block_rows) / 8;
+    let in_gob = ((byte % 64) / 32) * 256
+        + ((y % 8) / 2) * 64
+        + ((byte % 32) / 16) * 32
+        + (y % 2) * 16
+        + byte % 16;
+
+    ((gob * 512 + in_gob) * 8 + bit % 8) / bits
+}
+
+/// Position of each pixel in Xbox 360 tiled layout, listed in row-major order.
+/// Both sides are padded to 32 pixels.
+fn xenos(w: usize, h: usize, bits: usize) -> Vec<usize> {
+    let (aligned_w, aligned_h) = (w.next_multiple_of(32), h.next_multiple_of(32));
+    let mut positions = vec![0; w * h];
+
+    for i in 0..aligned_w * aligned_h {
+        let (x, y) = xenos_coords(i, aligned_w, bits / 8);
+        if x < w && y < h {
+            positions[y * w + x] = i;
+        }
+    }
+
+    positions
+}
+
+/// Coordinates of the pixel stored at `offset`, in pixels of `pitch` bytes, as
+/// computed by `XGAddress2DTiledX` and `XGAddress2DTiledY`.
+fn xenos_coords(offset: usize, aligned_w: usize, pitch: usize) -> (usize, usize) {
+    let log_bpp = pitch.trailing_zeros() as usize;
+    let offset_b = offset << log_bpp;
+    let offset_t = ((offset_b & !4095) >> 3) + ((offset_b & 1792) >> 2) + (offset_b & 63);
+    let offset_m = offset_t >> (7 + log_bpp);
+
+    let macro_x = (offset_m % (aligned_w >> 5)) << 2;
+    let tile_x = (((offset_t >> (5 + log_bpp)) & 2) + (offset_b >> 6)) & 3;
+    let micro_x = ((((offset_t >> 1) & !15) + (offset_t & 15)) & ((pitch << 3) - 1)) >> log_bpp;
+
+    let macro_y = (offset_m / (aligned_w >> 5)) << 2;
+    let tile_y = ((offset_t >> (6 + log_bpp)) & 1) + ((offset_b & 2048) >> 10);
+    let micro_y =
+        (((offset_t & (((pitch << 6) - 1) & !31)) + ((offset_t & 15) << 1)) >> (3 + log_bpp)) & !1;
+
+    (
+        ((macro_x + tile_x) << 3) + micro_x,
+        ((macro_y + tile_y) << 3) + micro_y + ((offset_t & 16) >> 4),
+    )
+}
+
+/// A Wii U surface, with its pitch padded as the GX2 address library does for
+/// its tile mode.
+struct Gx2Surface {
+    tile_mode: Gx2TileMode,
+    bits: usize,
+    pitch: usize,
+    pipe_swizzle: usize,
+    bank_swizzle: usize,
+    bank_swap_width: usize,
+}
+
+impl Gx2Surface {
+    fn new(tile_mode: Gx2TileMode, w: usize, bits: usize, swizzle: usize) -> Self {
+        let (macro_w, _) = Self::macro_tile_size(tile_mode);
+        let pitch_align = match tile_mode {
+            Gx2TileMode::LinearAligned => (8 * GX2_GROUP_SIZE / bits).max(64),
+            Gx2TileMode::Tiled1DThin1 => (GX2_GROUP_SIZE / bits).max(8),
+            _ => macro_w.max(macro_w * (GX2_GROUP_SIZE / bits / 8)),
+        };
+        let pitch = w.max(1).next_multiple_of(pitch_align);
+
+        let bank_swap_width = if tile_mode.is_bank_swapped() {
+            Self::bank_swap_width(tile_mode, bits, pitch)
+        } else {
+            0
+        };
+
+        Self {
+            tile_mode,
+            bits,
+            pitch,
+            pipe_swizzle: (swizzle >> 8) & 1,
+            bank_swizzle: (swizzle >> 9) & 3,
+            bank_swap_width,
+        }
+    }
+
+    fn macro_tile_size(tile_mode: Gx2TileMode) -> (usize, usize) {
+        let ratio = tile_mode.aspect_ratio();
+
+        (8 * GX2_BANKS / ratio, 8 * GX2_PIPES * ratio)
+    }
+
+    /// Width in pixels after which 2B tile modes swap banks.
+    fn bank_swap_width(tile_mode: Gx2TileMode, bits: usize, pitch: usize) -> usize {
+        let bytes_per_sample = 8 * bits;
+        let slices_per_tile = (1 / (GX2_SPLIT_SIZE / bytes_per_sample)).max(1);
+        let bytes_per_tile_slice = bytes_per_sample / slices_per_tile;
+
+        let swap_tiles = ((GX2_SWAP_SIZE >> 1) / bits).max(1);
+        let swap_width = swap_tiles * 8 * GX2_BANKS;
+        let height_bytes = tile_mode.aspect_ratio() * GX2_PIPES * bits / slices_per_tile;
+        let swap_max = GX2_PIPES * GX2_BANKS * GX2_ROW_SIZE / height_bytes;
+        let swap_min = GX2_GROUP_SIZE * 8 * GX2_BANKS / bytes_per_tile_slice;
+
+        let mut bank_swap_width = swap_max.min(swap_min.max(swap_width));
+        while bank_swap_width >= 2 * pitch {
+            bank_swap_width >>= 1;
+        }
+
+        bank_swap_width
+    }
+
+    fn position(&self, x: usize, y: usize) -> usize {
+        let pixel = self.pixel_index(x, y);
+
+        match self.tile_mode {
+            Gx2TileMode::LinearAligned => y * self.pitch + x,
+            Gx2TileMode::Tiled1DThin1 => ((y / 8) * (self.pitch / 8) + x / 8) * 64 + pixel,
+            _ => self.macro_tiled_address(x, y, pixel) * 8 / self.bits,
+        }
+    }
+
+    /// Index of a pixel inside its 8x8 micro tile, interleaving the bits of its
+    /// coordinates in an order that depends on the pixel size.
+    fn pixel_index(&self, x: usize, y: usize) -> usize {
+        let (x0, x1, x2) = (x & 1, (x >> 1) & 1, (x >> 2) & 1);
+        let (y0, y1, y2) = (y & 1, (y >> 1) & 1, (y >> 2) & 1);
+
+        let bits = match self.bits {
+            8 => [x0, x1, x2, y1, y0, y2],
+            16 => [x0, x1, x2, y0, y1, y2],
+            64 => [x0, y0, x1, x2, y1, y2],
+            128 => [y0, x0, x1, x2, y1, y2],
+            _ => [x0, x1, y0, x2, y1, y2],
+        };
+
+        bits.iter()
+            .enumerate()
+            .fold(0, |index, (i, bit)| index | (bit << i))
+    }
+
+    /// Byte address of a pixel in a macro-tiled surface. Macro tiles are spread
+    /// over pipes and banks picked from the pixel coordinates and the surface
+    /// swizzle, and interleaved every 256 bytes.
+    fn macro_tiled_address(&self, x: usize, y: usize, pixel: usize) -> usize {
+        let elem_offset = self.bits * pixel / 8;
+
+        let pipe = ((y >> 3) ^ (x >> 3)) & 1;
+        let bank = (((y / (16 * GX2_PIPES)) ^ (x >> 3)) & 1)
+            | ((((y / (8 * GX2_PIPES)) ^ (x >> 4)) & 1) << 1);
+        let swizzle = self.pipe_swizzle + GX2_PIPES * self.bank_swizzle;
+        let bank_pipe = ((pipe + GX2_PIPES * bank) ^ swizzle) % (GX2_PIPES * GX2_BANKS);
+        let pipe = bank_pipe % GX2_PIPES;
+        let mut bank = bank_pipe / GX2_PIPES;
+
+        let (macro_w, macro_h) = Self::macro_tile_size(self.tile_mode);
+        let macro_tile_bytes = self.bits * macro_w * macro_h / 8;
+        let (macro_x, macro_y) = (x / macro_w, y / macro_h);
+        let macro_offset = (macro_x + (self.pitch / macro_w) * macro_y) * macro_tile_bytes;
+
+        if self.tile_mode.is_bank_swapped() {
+            const BANK_SWAP_ORDER: [usize; 4] = [0, 1, 3, 2];
+
+            let swap_index = macro_w * macro_x / self.bank_swap_width;
+            bank ^= BANK_SWAP_ORDER[swap_index % GX2_BANKS];
+        }
+
+        let total = elem_offset + (macro_offset >> 3);
+        let group_mask = GX2_GROUP_SIZE - 1;
+
+        (bank << 9) | (pipe << 8) | (total & group_mask) | ((total & !group_mask) << 3)
+    }
+}
+
+/// Index of pixel `(x, y)` in Morton order, with `x` in the low bit of each pair,
+/// in a `w` x `h` image with power-of-two sides. Bits of the longer side beyond
+/// the shorter one are appended as is, so the image is a row or column of square
+/// Morton tiles.
+fn morton(x: usize, y: usize, w: usize, h: usize) -> usize {
+    let mut index = 0;
+    let mut bit = 0;
+
+    while 1 << bit < w.min(h) {
+        index |= ((x >> bit) & 1) << (2 * bit);
+        index |= ((y >> bit) & 1) << (2 * bit + 1);
+        bit += 1;
+    }
+
+    let rest = if h < w { x } else { y } >> bit;
+
+    index | (rest << (2 * bit))
+}
+
+/// Swaps entries 8-15 and 16-23 of every 32 entries of a PS2 CLUT. Entries past
+/// the last full group of 32 are left as is.
+pub fn ps2_clut(rgba: &mut [u8]) {
+    for group in rgba.chunks_exact_mut(32 * 4) {
+        let (first, second) = group[8 * 4..24 * 4].split_at_mut(8 * 4);
+        first.swap_with_slice(second);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::image::unswizzle;
+    use crate::app::image_format::BitOrder;
+
+    /// PCSX2's `blockTable32` and `blockTable8`.
+    const BLOCKS_32: [[usize; 8]; 4] = [
+        [0, 1, 4, 5, 16, 17, 20, 21],
+        [2, 3, 6, 7, 18, 19, 22, 23],
+        [8, 9, 12, 13, 24, 25, 28, 29],
+        [10, 11, 14, 15, 26, 27, 30, 31],
+    ];
+
+    /// PCSX2's `blockTable4`.
+    const BLOCKS_4: [[usize; 4]; 8] = [
+        [0, 2, 8, 10],
+        [1, 3, 9, 11],
+        [4, 6, 12, 14],
+        [5, 7, 13, 15],
+        [16, 18, 24, 26],
+        [17, 19, 25, 27],
+        [20, 22, 28, 30],
+        [21, 23, 29, 31],
+    ];
+
+    /// The first 8 rows of PCSX2's `columnTable32`.
+    const COLUMNS_32: [[usize; 8]; 8] = [
+        [0, 1, 4, 5, 8, 9, 12, 13],
+        [2, 3, 6, 7, 10, 11, 14, 15],
+        [16, 17, 20, 21, 24, 25, 28, 29],
+        [18, 19, 22, 23, 26, 27, 30, 31],
+        [32, 33, 36, 37, 40, 41, 44, 45],
+        [34, 35, 38, 39, 42, 43, 46, 47],
+        [48, 49, 52, 53, 56, 57, 60, 61],
+        [50, 51, 54, 55, 58, 59, 62, 63],
+    ];
+
+    /// The first 8 rows of PCSX2's `columnTable8`. The last 8 add 128.
+    #[rustfmt::skip]
+    const COLUMNS_8: [[usize; 16]; 8] = [
+        [ 0,   4,  16,  20,  32,  36,  48,  52,   2,   6,  18,  22,  34,  38,  50,  54],
+        [ 8,  12,  24,  28,  40,  44,  56,  60,  10,  14,  26,  30,  42,  46,  58,  62],
+        [33,  37,  49,  53,   1,   5,  17,  21,  35,  39,  51,  55,   3,   7,  19,  23],
+        [41,  45,  57,  61,   9,  13,  25,  29,  43,  47,  59,  63,  11,  15,  27,  31],
+        [96, 100, 112, 116,  64,  68,  80,  84,  98, 102, 114, 118,  66,  70,  82,  86],
+        [104, 108, 120, 124, 72,  76,  88,  92, 106, 110, 122, 126,  74,  78,  90,  94],
+        [65,  69,  81,  85,  97, 101, 113, 117,  67,  71,  83,  87,  99, 103, 115, 119],
+        [73,  77,  89,  93, 105, 109, 121, 125,  75,  79,  91,  95, 107, 111, 123, 127],
+    ];
+
+    /// The first 8 rows of PCSX2's `columnTable4`. The last 8 add 256.
+    #[rustfmt::skip]
+    const COLUMNS_4: [[usize; 32]; 8] = [
+        [  0,   8,  32,  40,  64,  72,  96, 104,   2,  10,  34,  42,  66,  74,  98, 106,   4,  12,  36,  44,  68,  76, 100, 108,   6,  14,  38,  46,  70,  78, 102, 110],
+        [ 16,  24,  48,  56,  80,  88, 112, 120,  18,  26,  50,  58,  82,  90, 114, 122,  20,  28,  52,  60,  84,  92, 116, 124,  22,  30,  54,  62,  86,  94, 118, 126],
+        [ 65,  73,  97, 105,   1,   9,  33,  41,  67,  75,  99, 107,   3,  11,  35,  43,  69,  77, 101, 109,   5,  13,  37,  45,  71,  79, 103, 111,   7,  15,  39,  47],
+        [ 81,  89, 113, 121,  17,  25,  49,  57,  83,  91, 115, 123,  19,  27,  51,  59,  85,  93, 117, 125,  21,  29,  53,  61,  87,  95, 119, 127,  23,  31,  55,  63],
+        [192, 200, 224, 232, 128, 136, 160, 168, 194, 202, 226, 234, 130, 138, 162, 170, 196, 204, 228, 236, 132, 140, 164, 172, 198, 206, 230, 238, 134, 142, 166, 174],
+        [208, 216, 240, 248, 144, 152, 176, 184, 210, 218, 242, 250, 146, 154, 178, 186, 212, 220, 244, 252, 148, 156, 180, 188, 214, 222, 246, 254, 150, 158, 182, 190],
+        [129, 137, 161, 169, 193, 201, 225, 233, 131, 139, 163, 171, 195, 203, 227, 235, 133, 141, 165, 173, 197, 205, 229, 237, 135, 143, 167, 175, 199, 207, 231, 239],
+        [145, 153, 177, 185, 209, 217, 241, 249, 147, 155, 179, 187, 211, 219, 243, 251, 149, 157, 181, 189, 213, 221, 245, 253, 151, 159, 183, 191, 215, 223, 247, 255],
+    ];
+
+    /// Word address of a PSMCT32 pixel, from the PCSX2 tables.
+    fn psmct32_address(x: usize, y: usize, w: usize) -> usize {
+        let page = (y / 32) * w.div_ceil(64) + x / 64;
+
+        page * 2048 + BLOCKS_32[(y % 32) / 8][(x % 64) / 8] * 64 + COLUMNS_32[y % 8][x % 8]
+    }
+
+    /// Byte address of a PSMT8 pixel, from the PCSX2 tables.
+    fn psmt8_address(x: usize, y: usize, w: usize) -> usize {
+        let page = (y / 64) * w.div_ceil(128) + x / 128;
+        let block = BLOCKS_32[(y % 64) / 16][(x % 128) / 16];
+
+        page * 8192 + block * 256 + (y % 16) / 8 * 128 + COLUMNS_8[y % 8][x % 16]
+    }
+
+    /// Nibble address of a PSMT4 pixel, from the PCSX2 tables.
+    fn psmt4_address(x: usize, y: usize, w: usize) -> usize {
+        let page = (y / 128) * w.div_ceil(128) + x / 128;
+        let block = BLOCKS_4[(y % 128) / 16][(x % 128) / 32];
+
+        page * 16384 + block * 512 + (y % 16) / 8 * 256 + COLUMNS_4[y % 8][x % 32]
+    }
+
+    /// Swizzles a `w` x `h` image of `bits` per pixel to the known `address` of
+    /// each pixel, then checks that unswizzling it with `swizzle` gives it back.
+    fn round_trip(
+        swizzle: Swizzle,
+        w: usize,
+        h: usize,
+        bits: usize,
+        address: impl Fn(usize, usize) -> usize,
+    ) {
+        let state = SwizzleState {
+            selected: swizzle,
+            ..Default::default()
+        };
+        let positions = positions(&state, w, h, bits).unwrap().unwrap();
+        // Scrambled so that misplaced pixels show
+        let value = |x: usize, y: usize| ((x * 7 + y * 13 + x * y) & ((1 << bits) - 1)) as u8;
+
+        let mut swizzled = vec![0; w * h * bits / 8];
+        let mut linear = vec![0; w * h * bits / 8];
+        for y in 0..h {
+            for x in 0..w {
+                let (src, dst) = (address(x, y), y * w + x);
+                swizzled[src * bits / 8] |= value(x, y) << (src * bits % 8);
+                linear[dst * bits / 8] |= value(x, y) << (dst * bits % 8);
+            }
+        }
+
+        assert_eq!(
+            unswizzle(&swizzled, &positions, bits, BitOrder::LsbFirst),
+            linear
+        );
+    }
+
+    #[test]
+    fn psmct32_gs_memory() {
+        round_trip(Swizzle::Ps2, 128, 64, 32, |x, y| psmct32_address(x, y, 128));
+    }
+
+    #[test]
+    fn psmt8_gs_memory() {
+        round_trip(Swizzle::Ps2, 256, 128, 8, |x, y| psmt8_address(x, y, 256));
+    }
+
+    #[test]
+    fn psmt4_gs_memory() {
+        round_trip(Swizzle::Ps2, 256, 256, 4, |x, y| psmt4_address(x, y, 256));
+    }
+
+    /// Checks against the `unswizzle8` routine shared by most PS2 texture tools.
+    #[test]
+    fn psmt8_ct32_upload() {
+        let w = 256;
+        round_trip(Swizzle::Ps2Ct32, w, 128, 8, |x, y| {
+            let block = (y & !0xF) * w + (x & !0xF) * 2;
+            let swap = (((y + 2) >> 2) & 1) * 4;
+            let row = (((y & !3) >> 1) + (y & 1)) & 7;
+            let column = row * w * 2 + ((x + swap) & 7) * 4;
+            let byte = ((y >> 1) & 1) + ((x >> 2) & 2);
+
+            block + column + byte
+        });
+    }
+
+    /// Writes the PSMT4 texture to GS memory and reads it back as PSMCT32, both
+    /// through the PCSX2 tables.
+    #[test]
+    fn psmt4_ct32_upload() {
+        let (w, h) = (256, 256);
+        let mut ct32_pixels = vec![0; w * h / 8];
+        for y in 0..h / 4 {
+            for x in 0..w / 2 {
+                ct32_pixels[psmct32_address(x, y, w / 2)] = y * (w / 2) + x;
+            }
+        }
+
+        round_trip(Swizzle::Ps2Ct32, w, h, 4, |x, y| {
+            let nibble = psmt4_address(x, y, w);
+            ct32_pixels[nibble / 8] * 8 + nibble % 8
+        });
+    }
+}
@@ -1,10 +1,14 @@
+use std::path::{Path, PathBuf};
+
 use iced::{
-    Element,
-    widget::{Row, column, radio, row},
+    Element, Length,
+    alignment::Vertical,
+    widget::{Column, Row, button, checkbox, column, radio, row, text, text_input},
 };
 
 use super::message::{Message, TextInput};
-use crate::SPACING;
+use super::pixel_format::PixelFormatState;
+use crate::{LABEL_WIDTH, SPACING};
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum ImageFormat {
@@ -13,10 +17,12 @@ pub enum ImageFormat {
     LinearIndexed,
     Tiled,
     TiledIndexed,
+    Compressed,
+    Gx,
 }
 
 impl ImageFormat {
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let linear = radio(
             "Linear",
             Self::Linear,
@@ -42,38 +48,121 @@ impl ImageFormat {
             Message::ImageFormatChanged,
         );
 
-        row![linear, linear_indexed, tiled, tiled_indexed]
+        let compressed = radio(
+            "Compressed",
+            Self::Compressed,
+            Some(*self),
+            Message::ImageFormatChanged,
+        );
+
+        let gx = radio("GX", Self::Gx, Some(*self), Message::ImageFormatChanged);
+
+        row![linear, linear_indexed, tiled, tiled_indexed, compressed, gx]
             .spacing(SPACING)
             .wrap()
             .into()
     }
+
+    /// GX packs nibbles high first, other formats low first.
+    pub fn default_bit_order(&self) -> BitOrder {
+        match self {
+            Self::Gx => BitOrder::MsbFirst,
+            _ => BitOrder::LsbFirst,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct PaletteInfo {
+    pub filepath: Option<PathBuf>,
+    pub file_format: PaletteFile,
     pub offset: String,
+    pub count: String,
     pub bpp: Bpp,
+    pub pixel_format: PixelFormatState,
+    pub ps2_clut: bool,
 }
 
 impl Default for PaletteInfo {
     fn default() -> Self {
         Self {
+            filepath: None,
+            file_format: Default::default(),
             offset: 0.to_string(),
+            count: Bpp::default().color_count().to_string(),
             bpp: Default::default(),
+            pixel_format: PixelFormatState::palette(),
+            ps2_clut: false,
         }
     }
 }
 
 impl PaletteInfo {
-    pub fn view(&self) -> Element<Message> {
-        let pal_view = TextInput::PaletteOffset.view("Palette offset:", &self.offset);
+    pub fn view(&self) -> Element<'_, Message> {
+        let ps2_clut_view = if self.is_parsed() {
+            None
+        } else {
+            Some(checkbox("PS2 CLUT unswizzle", self.ps2_clut).on_toggle(Message::Ps2ClutChanged))
+        };
         let bpp_view = self.bpp.view();
 
-        column![pal_view, bpp_view].spacing(SPACING).into()
+        self.source_view()
+            .push_maybe(ps2_clut_view)
+            .push(bpp_view)
+            .into()
     }
 
-    pub fn color_count(&self) -> usize {
-        self.bpp.color_count()
+    /// Where the colors are read from: the palette file and its format, and the
+    /// offset and count of raw palettes.
+    pub fn source_view(&self) -> Column<'_, Message> {
+        let file_view = self.file_view();
+        let file_format_view = self.filepath.as_ref().map(|_| self.file_format.view());
+        let raw_view = if self.is_parsed() {
+            None
+        } else {
+            let pal_view = TextInput::PaletteOffset.view("Palette offset:", &self.offset);
+            let count_view = TextInput::PaletteCount.view("Palette count:", &self.count);
+
+            Some(row![pal_view, count_view].spacing(SPACING))
+        };
+
+        column![file_view]
+            .push_maybe(file_format_view)
+            .push_maybe(raw_view)
+            .spacing(SPACING)
+    }
+
+    /// Path of the palette file, or the image file itself when none is picked.
+    fn file_view(&self) -> Row<'_, Message> {
+        use iced::widget::text_input::Status;
+
+        let path = self
+            .filepath
+            .as_deref()
+            .and_then(Path::to_str)
+            .unwrap_or_default();
+
+        let label = text("Palette file:").width(LABEL_WIDTH);
+        let input = text_input("same as image", path)
+            .width(Length::Fill)
+            .style(|theme, _| text_input::default(theme, Status::Active));
+        let pick = button("...").on_press(Message::PickPaletteFile);
+        let clear = button("x")
+            .on_press_maybe(self.filepath.as_ref().map(|_| Message::ClearPaletteFile))
+            .style(button::secondary);
+
+        row![label, input, pick, clear]
+            .spacing(SPACING)
+            .align_y(Vertical::Center)
+    }
+
+    /// Whether the colors come from a palette file format instead of raw pixels.
+    pub fn is_parsed(&self) -> bool {
+        self.filepath.is_some() && self.file_format != PaletteFile::Raw
+    }
+
+    pub fn color_count(&self) -> Result<usize, std::num::ParseIntError> {
+        self.count.parse()
     }
 
     pub fn offset(&self) -> Result<usize, std::num::ParseIntError> {
@@ -83,31 +172,147 @@ impl PaletteInfo {
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Bpp {
+    Bpp1,
+    Bpp2,
     Bpp4,
     #[default]
     Bpp8,
+    Bpp16,
 }
 
 impl Bpp {
-    fn view(&self) -> Row<Message> {
+    fn view(&self) -> Row<'_, Message> {
+        let bpp1 = radio("1bpp", Self::Bpp1, Some(*self), Message::PaletteBppChanged);
+        let bpp2 = radio("2bpp", Self::Bpp2, Some(*self), Message::PaletteBppChanged);
         let bpp4 = radio("4bpp", Self::Bpp4, Some(*self), Message::PaletteBppChanged);
         let bpp8 = radio("8bpp", Self::Bpp8, Some(*self), Message::PaletteBppChanged);
+        let bpp16 = radio(
+            "16bpp",
+            Self::Bpp16,
+            Some(*self),
+            Message::PaletteBppChanged,
+        );
 
-        row![bpp4, bpp8].spacing(SPACING)
+        row![bpp1, bpp2, bpp4, bpp8, bpp16].spacing(SPACING)
     }
 
-    fn color_count(&self) -> usize {
+    pub fn bits(&self) -> usize {
         match self {
-            Bpp::Bpp4 => 16,
-            Bpp::Bpp8 => 256,
+            Bpp::Bpp1 => 1,
+            Bpp::Bpp2 => 2,
+            Bpp::Bpp4 => 4,
+            Bpp::Bpp8 => 8,
+            Bpp::Bpp16 => 16,
         }
     }
+
+    pub fn color_count(&self) -> usize {
+        1 << self.bits()
+    }
+}
+
+/// Format of an external palette file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaletteFile {
+    /// Pixels at the palette offset, in the palette pixel format.
+    #[default]
+    Raw,
+    JascPal,
+    Act,
+    Gpl,
+    Png,
+}
+
+impl PaletteFile {
+    /// Guesses the format from the file extension, falling back to raw.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("pal") => Self::JascPal,
+            Some("act") => Self::Act,
+            Some("gpl") => Self::Gpl,
+            Some("png") => Self::Png,
+            _ => Self::Raw,
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let raw = radio("Raw", Self::Raw, Some(*self), Message::PaletteFileChanged);
+        let jasc_pal = radio(
+            "JASC-PAL",
+            Self::JascPal,
+            Some(*self),
+            Message::PaletteFileChanged,
+        );
+        let act = radio("ACT", Self::Act, Some(*self), Message::PaletteFileChanged);
+        let gpl = radio("GPL", Self::Gpl, Some(*self), Message::PaletteFileChanged);
+        let png = radio("PNG", Self::Png, Some(*self), Message::PaletteFileChanged);
+
+        row![raw, jasc_pal, act, gpl, png]
+            .spacing(SPACING)
+            .wrap()
+            .into()
+    }
+}
+
+/// Order of pixels packed into a byte, starting from its low or high bits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    #[default]
+    LsbFirst,
+    MsbFirst,
+}
+
+impl BitOrder {
+    pub fn view(&self) -> Row<'_, Message> {
+        let label = text("Bit order:").width(LABEL_WIDTH);
+        let lsb = radio(
+            "LSB first",
+            Self::LsbFirst,
+            Some(*self),
+            Message::BitOrderChanged,
+        );
+        let msb = radio(
+            "MSB first",
+            Self::MsbFirst,
+            Some(*self),
+            Message::BitOrderChanged,
+        );
+
+        row![label, lsb, msb].spacing(SPACING)
+    }
+}
+
+/// Order of pixels inside a tile, or of tiles across the image.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TileOrder {
+    #[default]
+    RowMajor,
+    Morton,
+}
+
+impl TileOrder {
+    fn view(&self, label: &'static str, on_change: fn(TileOrder) -> Message) -> Row<'_, Message> {
+        let label = text(label).width(LABEL_WIDTH);
+        let row_major = radio("Row-major", Self::RowMajor, Some(*self), on_change);
+        let morton = radio("Morton", Self::Morton, Some(*self), on_change);
+
+        row![label, row_major, morton]
+            .spacing(SPACING)
+            .align_y(Vertical::Center)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct TileInfo {
     pub width: String,
     pub height: String,
+    pub pixel_order: TileOrder,
+    pub tile_order: TileOrder,
 }
 
 impl Default for TileInfo {
@@ -115,16 +320,28 @@ impl Default for TileInfo {
         Self {
             width: 2.to_string(),
             height: 2.to_string(),
+            pixel_order: Default::default(),
+            tile_order: Default::default(),
         }
     }
 }
 
 impl TileInfo {
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let width = TextInput::TileWidth.view("Tile width:", &self.width);
         let height = TextInput::TileHeight.view("Tile height:", &self.height);
+        let pixel_order = self
+            .pixel_order
+            .view("Pixels:", Message::TilePixelOrderChanged);
+        let tile_order = self.tile_order.view("Tiles:", Message::TileOrderChanged);
 
-        row![width, height].spacing(SPACING).into()
+        column![
+            row![width, height].spacing(SPACING),
+            pixel_order,
+            tile_order
+        ]
+        .spacing(SPACING)
+        .into()
     }
 
     pub fn width(&self) -> Result<usize, std::num::ParseIntError> {
@@ -4,8 +4,11 @@ use iced::{
 };
 
 use super::{
-    image_format::{Bpp, ImageFormat},
-    pixel_format::{Endian, PixelFormat},
+    compressed_format::CompressedFormat,
+    gx_format::{GxFormat, GxPaletteFormat},
+    image_format::{BitOrder, Bpp, ImageFormat, PaletteFile, TileOrder},
+    pixel_format::{CustomField, Endian, IntegerOption, PixelFormat},
+    swizzle::{Gx2TileMode, Swizzle},
 };
 use crate::{LABEL_WIDTH, SPACING};
 
@@ -16,9 +19,31 @@ pub enum Message {
     PixelFormatChanged(PixelFormat),
     OrderChanged(String),
     EndianChanged(Endian),
+    CustomChanged(CustomField, String),
+    IntegerChanged(IntegerOption),
     IgnoreAlphaChanged(bool),
     ImageFormatChanged(ImageFormat),
+    CompressedFormatChanged(CompressedFormat),
+    ReconstructZChanged(bool),
+    ExposureChanged(f32),
+    GxFormatChanged(GxFormat),
+    GxPaletteFormatChanged(GxPaletteFormat),
+    PickPaletteFile,
+    ClearPaletteFile,
+    PaletteFileChanged(PaletteFile),
     PaletteBppChanged(Bpp),
+    PalettePixelFormatChanged(PixelFormat),
+    PaletteOrderChanged(String),
+    PaletteEndianChanged(Endian),
+    PaletteCustomChanged(CustomField, String),
+    PaletteIntegerChanged(IntegerOption),
+    BitOrderChanged(BitOrder),
+    Ps2ClutChanged(bool),
+    SwizzleChanged(Swizzle),
+    SwapWordsChanged(bool),
+    Gx2TileModeChanged(Gx2TileMode),
+    TilePixelOrderChanged(TileOrder),
+    TileOrderChanged(TileOrder),
     ProcessImage,
     SaveImage(SaveFormat),
     FilterChanged(FilterMethod),
@@ -32,10 +57,15 @@ pub enum TextInput {
     PaletteOffset,
     TileWidth,
     TileHeight,
+    PaletteCount,
+    BlockWidth,
+    BlockHeight,
+    GobBlockHeight,
+    Gx2Swizzle,
 }
 
 impl TextInput {
-    pub fn view(&self, label: &'static str, input: &str) -> Row<Message> {
+    pub fn view(&self, label: &'static str, input: &str) -> Row<'_, Message> {
         let label = text(label).width(LABEL_WIDTH);
         let input = text_input("", input)
             .on_input(|new_value| Message::TextInputChanged(*self, new_value))
@@ -47,18 +77,39 @@ impl TextInput {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum SaveFormat {
     Rgba,
     #[default]
     Png,
+    IndexedPng,
+    JascPal,
+    Act,
+    Gpl,
+    Swatch,
 }
 
 impl SaveFormat {
     pub fn extension(&self) -> &'static [&'static str] {
         match self {
             SaveFormat::Rgba => &[],
-            SaveFormat::Png => &["png"],
+            SaveFormat::Png | SaveFormat::IndexedPng | SaveFormat::Swatch => &["png"],
+            SaveFormat::JascPal => &["pal"],
+            SaveFormat::Act => &["act"],
+            SaveFormat::Gpl => &["gpl"],
         }
     }
+
+    /// Whether the format saves the palette instead of the image.
+    pub fn is_palette(&self) -> bool {
+        matches!(
+            self,
+            SaveFormat::JascPal | SaveFormat::Act | SaveFormat::Gpl | SaveFormat::Swatch
+        )
+    }
+
+    /// Whether the format needs the indices and palette of an indexed image.
+    pub fn is_indexed(&self) -> bool {
+        *self == SaveFormat::IndexedPng || self.is_palette()
+    }
 }
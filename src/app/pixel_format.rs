@@ -1,6 +1,6 @@
 use iced::{
     alignment::Vertical,
-    widget::{Column, Row, column, combo_box, radio, row, text, text_input},
+    widget::{Column, Row, checkbox, column, combo_box, radio, row, text, text_input},
 };
 
 use super::message::Message;
@@ -13,6 +13,13 @@ pub struct PixelFormatState {
     pub selected: PixelFormat,
     pub component_order: String,
     pub endian: Endian,
+    pub custom: CustomFormat,
+    pub integer: IntegerDisplay,
+    on_format: fn(PixelFormat) -> Message,
+    on_order: fn(String) -> Message,
+    on_endian: fn(Endian) -> Message,
+    on_custom: fn(CustomField, String) -> Message,
+    on_integer: fn(IntegerOption) -> Message,
 }
 
 impl Default for PixelFormatState {
@@ -23,26 +30,39 @@ impl Default for PixelFormatState {
             selected: default,
             component_order: default.default_order(),
             endian: Default::default(),
+            custom: Default::default(),
+            integer: Default::default(),
+            on_format: Message::PixelFormatChanged,
+            on_order: Message::OrderChanged,
+            on_endian: Message::EndianChanged,
+            on_custom: Message::CustomChanged,
+            on_integer: Message::IntegerChanged,
         }
     }
 }
 
 impl PixelFormatState {
-    pub fn view(&self) -> Column<Message> {
+    /// State for palette colors, whose widgets send the palette messages.
+    pub fn palette() -> Self {
+        Self {
+            on_format: Message::PalettePixelFormatChanged,
+            on_order: Message::PaletteOrderChanged,
+            on_endian: Message::PaletteEndianChanged,
+            on_custom: Message::PaletteCustomChanged,
+            on_integer: Message::PaletteIntegerChanged,
+            ..Default::default()
+        }
+    }
+
+    pub fn view(&self) -> Column<'_, Message> {
         let label = text("Format:").width(LABEL_WIDTH);
-        let combo_box = combo_box(
-            &self.state,
-            "",
-            Some(&self.selected),
-            Message::PixelFormatChanged,
-        )
-        .width(80);
+        let combo_box = combo_box(&self.state, "", Some(&self.selected), self.on_format).width(80);
 
         let order: Option<Row<Message>> = if self.is_orderable() {
             let label = text("Order:").width(LABEL_WIDTH);
             let input = text_input("", &self.component_order)
                 .width(80)
-                .on_input(Message::OrderChanged);
+                .on_input(self.on_order);
 
             row![label, input]
                 .spacing(SPACING)
@@ -52,8 +72,20 @@ impl PixelFormatState {
             None
         };
 
-        let endian: Option<Row<Message>> = if self.selected.use_endian() {
-            self.endian.view().into()
+        let endian: Option<Row<Message>> = if self.use_endian() {
+            self.endian.view(self.on_endian).into()
+        } else {
+            None
+        };
+
+        let custom: Option<Column<Message>> = if self.selected == Custom {
+            self.custom.view(self.on_custom).into()
+        } else {
+            None
+        };
+
+        let integer: Option<Column<Message>> = if self.selected.is_integer() {
+            self.integer.view(self.on_integer).into()
         } else {
             None
         };
@@ -63,12 +95,274 @@ impl PixelFormatState {
             .spacing(SPACING)
             .align_y(Vertical::Center);
 
-        column![row].push_maybe(endian).spacing(SPACING)
+        column![row]
+            .push_maybe(custom)
+            .push_maybe(endian)
+            .push_maybe(integer)
+            .spacing(SPACING)
     }
 
     pub fn is_orderable(&self) -> bool {
         self.selected.is_orderable()
     }
+
+    /// Whether pixels are narrower than a byte, so that several share one.
+    pub fn is_sub_byte(&self) -> bool {
+        self.bits_per_pixel().is_ok_and(|bits| bits < 8)
+    }
+
+    /// Whether the endianness applies, which isn't the case for sub-byte pixels.
+    pub fn use_endian(&self) -> bool {
+        self.selected.use_endian() && !self.is_sub_byte()
+    }
+
+    pub fn bits_per_pixel(&self) -> Result<usize, String> {
+        match self.selected {
+            Custom => self.custom.bits(),
+            format => Ok(format.bytes_per_pixel() * 8),
+        }
+    }
+}
+
+/// Field of a custom pixel format. Channels are indexed in RGBA order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomField {
+    Bits,
+    Offset(usize),
+    Width(usize),
+}
+
+/// Pixel format made of up to 64 bits, with each channel at its own bit offset
+/// and width. Channels with a width of zero are black, or opaque for alpha.
+#[derive(Debug)]
+pub struct CustomFormat {
+    pub bits: String,
+    pub offsets: [String; 4],
+    pub widths: [String; 4],
+}
+
+impl Default for CustomFormat {
+    fn default() -> Self {
+        Self {
+            bits: 32.to_string(),
+            offsets: [0, 8, 16, 24].map(|offset: usize| offset.to_string()),
+            widths: [8, 8, 8, 8].map(|width: usize| width.to_string()),
+        }
+    }
+}
+
+impl CustomFormat {
+    const CHANNELS: [char; 4] = ['R', 'G', 'B', 'A'];
+
+    pub fn view(&self, on_change: fn(CustomField, String) -> Message) -> Column<'_, Message> {
+        let input = |field: CustomField, value: &str, width: u16| {
+            text_input("", value)
+                .on_input(move |new_value| on_change(field, new_value))
+                .width(width)
+        };
+
+        let bits = row![
+            text("Bits:").width(LABEL_WIDTH),
+            input(CustomField::Bits, &self.bits, 80)
+        ]
+        .spacing(SPACING)
+        .align_y(Vertical::Center);
+
+        let channels = Self::CHANNELS.iter().enumerate().map(|(i, name)| {
+            row![
+                text(format!("{name} offset:")).width(LABEL_WIDTH),
+                input(CustomField::Offset(i), &self.offsets[i], 50),
+                text("width:"),
+                input(CustomField::Width(i), &self.widths[i], 50),
+            ]
+            .spacing(SPACING)
+            .align_y(Vertical::Center)
+            .into()
+        });
+
+        column![bits].extend(channels).spacing(SPACING)
+    }
+
+    pub fn set(&mut self, field: CustomField, value: String) {
+        match field {
+            CustomField::Bits => self.bits = value,
+            CustomField::Offset(i) => self.offsets[i] = value,
+            CustomField::Width(i) => self.widths[i] = value,
+        }
+    }
+
+    pub fn bits(&self) -> Result<usize, String> {
+        match self.bits.parse() {
+            Ok(bits @ 1..=64) => Ok(bits),
+            Ok(_) => Err("bits per pixel must be between 1 and 64".into()),
+            Err(_) => Err("bits per pixel is empty".into()),
+        }
+    }
+
+    /// Bits per pixel, and the offset and width of each channel in RGBA order.
+    pub fn layout(&self) -> Result<(usize, [(usize, usize); 4]), String> {
+        let bits = self.bits()?;
+        let mut channels = [(0, 0); 4];
+
+        for (i, (channel, name)) in channels.iter_mut().zip(Self::CHANNELS).enumerate() {
+            let offset: usize = self.offsets[i]
+                .parse()
+                .map_err(|_| format!("{name} offset is empty"))?;
+            let width: usize = self.widths[i]
+                .parse()
+                .map_err(|_| format!("{name} width is empty"))?;
+
+            if width > 0 && offset + width > bits {
+                return Err(format!("{name} channel does not fit in {bits} bits"));
+            }
+
+            *channel = (offset, width);
+        }
+
+        Ok((bits, channels))
+    }
+}
+
+/// How integer channels are mapped down to 8 bits for display.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IntegerMapping {
+    /// The whole range of the type.
+    #[default]
+    FullRange,
+    /// The lowest to the highest color value of the image.
+    Auto,
+    /// A window of values set by hand.
+    Window,
+}
+
+#[derive(Debug, Clone)]
+pub enum IntegerOption {
+    Signed(bool),
+    Mapping(IntegerMapping),
+    Min(String),
+    Max(String),
+}
+
+/// Signedness and display mapping of integer channel formats.
+#[derive(Debug, Default)]
+pub struct IntegerDisplay {
+    pub signed: bool,
+    pub mapping: IntegerMapping,
+    pub min: String,
+    pub max: String,
+}
+
+impl IntegerDisplay {
+    pub fn view(&self, on_change: fn(IntegerOption) -> Message) -> Column<'_, Message> {
+        let signed = checkbox("Signed", self.signed)
+            .on_toggle(move |val| on_change(IntegerOption::Signed(val)));
+
+        let mapping = |label, value| {
+            radio(label, value, Some(self.mapping), move |mapping| {
+                on_change(IntegerOption::Mapping(mapping))
+            })
+        };
+        let mappings = row![
+            text("Display:").width(LABEL_WIDTH),
+            mapping("Full range", IntegerMapping::FullRange),
+            mapping("Auto", IntegerMapping::Auto),
+            mapping("Window", IntegerMapping::Window),
+        ]
+        .spacing(SPACING);
+
+        let window: Option<Row<Message>> = if self.mapping == IntegerMapping::Window {
+            row![
+                text("Min:").width(LABEL_WIDTH),
+                text_input("", &self.min)
+                    .on_input(move |value| on_change(IntegerOption::Min(value)))
+                    .width(80),
+                text("max:"),
+                text_input("", &self.max)
+                    .on_input(move |value| on_change(IntegerOption::Max(value)))
+                    .width(80),
+            ]
+            .spacing(SPACING)
+            .align_y(Vertical::Center)
+            .into()
+        } else {
+            None
+        };
+
+        column![signed, mappings]
+            .push_maybe(window)
+            .spacing(SPACING)
+    }
+
+    /// Applies an option. Window bounds must be integers, possibly negative, or
+    /// empty.
+    pub fn set(&mut self, option: IntegerOption) {
+        let is_integer = |value: &str| {
+            let digits = value.strip_prefix('-').unwrap_or(value);
+            digits.chars().all(|chr| chr.is_ascii_digit())
+        };
+
+        match option {
+            IntegerOption::Signed(val) => self.signed = val,
+            IntegerOption::Mapping(mapping) => self.mapping = mapping,
+            IntegerOption::Min(value) if is_integer(&value) => self.min = value,
+            IntegerOption::Max(value) if is_integer(&value) => self.max = value,
+            _ => {}
+        }
+    }
+
+    /// Reads a channel of 2 or 4 bytes.
+    pub fn read(&self, bytes: &[u8], endian: Endian) -> i64 {
+        match (bytes, endian) {
+            (&[a, b], Endian::LE) => self.extend(u16::from_le_bytes([a, b]) as u64, 16),
+            (&[a, b], Endian::BE) => self.extend(u16::from_be_bytes([a, b]) as u64, 16),
+            (&[a, b, c, d], Endian::LE) => self.extend(u32::from_le_bytes([a, b, c, d]) as u64, 32),
+            (&[a, b, c, d], Endian::BE) => self.extend(u32::from_be_bytes([a, b, c, d]) as u64, 32),
+            _ => unreachable!(),
+        }
+    }
+
+    fn extend(&self, value: u64, bits: u32) -> i64 {
+        if self.signed {
+            ((value << (64 - bits)) as i64) >> (64 - bits)
+        } else {
+            value as i64
+        }
+    }
+
+    /// Range of `bits` wide values that the full range mapping spans.
+    pub fn full_range(&self, bits: u32) -> (i64, i64) {
+        if self.signed {
+            (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+        } else {
+            (0, (1 << bits) - 1)
+        }
+    }
+
+    /// Values shown as black and full intensity, given the color values of the
+    /// image.
+    pub fn window(
+        &self,
+        bits: u32,
+        values: impl Iterator<Item = i64> + Clone,
+    ) -> Result<(i64, i64), String> {
+        match self.mapping {
+            IntegerMapping::FullRange => Ok(self.full_range(bits)),
+            IntegerMapping::Auto => Ok((
+                values.clone().min().unwrap_or_default(),
+                values.max().unwrap_or_default(),
+            )),
+            IntegerMapping::Window => {
+                let min: i64 = self.min.parse().map_err(|_| "window min is empty")?;
+                let max: i64 = self.max.parse().map_err(|_| "window max is empty")?;
+
+                if max <= min {
+                    return Err("window max must be greater than min".into());
+                }
+
+                Ok((min, max))
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -79,10 +373,10 @@ pub enum Endian {
 }
 
 impl Endian {
-    pub fn view(&self) -> Row<Message> {
+    pub fn view(&self, on_change: fn(Endian) -> Message) -> Row<'_, Message> {
         let label = text("Endian:").width(LABEL_WIDTH);
-        let le = radio("LE", Self::LE, Some(*self), Message::EndianChanged);
-        let be = radio("BE", Self::BE, Some(*self), Message::EndianChanged);
+        let le = radio("LE", Self::LE, Some(*self), on_change);
+        let be = radio("BE", Self::BE, Some(*self), on_change);
 
         row![label, le, be].spacing(SPACING)
     }
@@ -102,11 +396,21 @@ pub enum PixelFormat {
     G8,
     B8,
     L8,
+
+    R16,
+    RG16,
+    RGBA16,
+    R32,
+
+    Custom,
 }
 
 impl PixelFormat {
     fn all() -> Vec<Self> {
-        vec![RGBA8888, RGB888, RGBA4444, RGBA5551, RGB565, R8, G8, B8, L8]
+        vec![
+            RGBA8888, RGB888, RGBA4444, RGBA5551, RGB565, R8, G8, B8, L8, R16, RG16, RGBA16, R32,
+            Custom,
+        ]
     }
 
     fn is_orderable(&self) -> bool {
@@ -114,11 +418,27 @@ impl PixelFormat {
     }
 
     pub fn use_alpha(&self) -> bool {
-        matches!(self, RGBA8888 | RGBA4444 | RGBA5551)
+        matches!(self, RGBA8888 | RGBA4444 | RGBA5551 | RGBA16 | Custom)
     }
 
     pub fn use_endian(&self) -> bool {
-        self.bytes_per_pixel() == 2
+        self.bytes_per_pixel() == 2 || self.is_integer() || *self == Custom
+    }
+
+    /// Formats with 16 or 32-bit integer channels.
+    pub fn is_integer(&self) -> bool {
+        matches!(self, R16 | RG16 | RGBA16 | R32)
+    }
+
+    /// Number of channels and bytes per channel of integer formats.
+    pub fn integer_channels(&self) -> (usize, usize) {
+        match self {
+            R16 => (1, 2),
+            RG16 => (2, 2),
+            RGBA16 => (4, 2),
+            R32 => (1, 4),
+            _ => (0, 0),
+        }
     }
 
     pub fn default_order(&self) -> String {
@@ -129,33 +449,28 @@ impl PixelFormat {
         }
     }
 
+    /// Checks `order` against the grammar of [`rgba_order`].
     pub fn valid_order(&self, order: &str) -> Option<Vec<char>> {
         let order: Vec<char> = order.to_ascii_lowercase().chars().collect();
 
         match self {
-            RGBA8888 | RGBA4444 | RGBA5551 => {
-                if order.len() == 4 && ['r', 'g', 'b', 'a'].iter().all(|chr| order.contains(chr)) {
-                    return Some(order);
-                }
-            }
-
-            RGB888 | RGB565 => {
-                if order.len() == 3 && ['r', 'g', 'b'].iter().all(|chr| order.contains(chr)) {
-                    return Some(order);
-                }
-            }
-            _ => return Some(vec![]),
+            RGBA8888 | RGBA4444 | RGBA5551 => rgba_order(&order).ok().map(|_| order),
+            RGB888 | RGB565 => rgb_order(&order).ok().map(|_| order),
+            _ => Some(vec![]),
         }
-
-        None
     }
 
+    /// Size of fixed formats. Custom formats set their own bits per pixel.
     pub fn bytes_per_pixel(&self) -> usize {
         match self {
             RGBA8888 => 4,
             RGB888 => 3,
             RGBA4444 | RGBA5551 | RGB565 => 2,
             R8 | G8 | B8 | L8 => 1,
+            R16 => 2,
+            RG16 | R32 => 4,
+            RGBA16 => 8,
+            Custom => 0,
         }
     }
 }
@@ -166,44 +481,116 @@ impl std::fmt::Display for PixelFormat {
     }
 }
 
-pub fn rgba_order(order: &[char]) -> Result<(usize, usize, usize, usize), String> {
-    let err_message = || String::from("invalid rgba order");
-
-    Ok((
-        order
-            .iter()
-            .position(|c| *c == 'r')
-            .ok_or_else(err_message)?,
-        order
-            .iter()
-            .position(|c| *c == 'g')
-            .ok_or_else(err_message)?,
-        order
-            .iter()
-            .position(|c| *c == 'b')
-            .ok_or_else(err_message)?,
-        order
-            .iter()
-            .position(|c| *c == 'a')
-            .ok_or_else(err_message)?,
-    ))
-}
-
-pub fn rgb_order(order: &[char]) -> Result<(usize, usize, usize), String> {
-    let err_message = || String::from("invalid rgb order");
-
-    Ok((
-        order
-            .iter()
-            .position(|c| *c == 'r')
-            .ok_or_else(err_message)?,
-        order
-            .iter()
-            .position(|c| *c == 'g')
-            .ok_or_else(err_message)?,
-        order
-            .iter()
-            .position(|c| *c == 'b')
-            .ok_or_else(err_message)?,
-    ))
+/// Where a channel takes its value from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Stored component at this index.
+    Component(usize),
+    Zero,
+    One,
+}
+
+impl Source {
+    /// Value of the channel, given the 8-bit components of a pixel.
+    pub fn value(&self, components: &[u8]) -> u8 {
+        match self {
+            Self::Component(i) => components[*i],
+            Self::Zero => 0,
+            Self::One => 255,
+        }
+    }
+}
+
+/// Parses a component order, with one character per stored component in
+/// storage order:
+/// - `r`, `g`, `b` or `a` reads it into that channel;
+/// - `x` skips it;
+/// - `0`, `1` or an already read channel skips it, and fills the next channel
+///   that isn't stored, in RGBA order, with that constant or channel.
+///
+/// Channels left unset are black, or opaque for alpha. For example "xRGB" skips
+/// a padding byte, "BGR0" has transparent alpha and "RRR1" shows red as gray.
+pub fn rgba_order(order: &[char]) -> Result<[Source; 4], String> {
+    channel_sources(order, "rgba").ok_or_else(|| String::from("invalid rgba order"))
+}
+
+/// Same as [`rgba_order`] without an alpha channel.
+pub fn rgb_order(order: &[char]) -> Result<[Source; 3], String> {
+    let [r, g, b, _] =
+        channel_sources(order, "rgb").ok_or_else(|| String::from("invalid rgb order"))?;
+
+    Ok([r, g, b])
+}
+
+fn channel_sources(order: &[char], channels: &str) -> Option<[Source; 4]> {
+    if order.is_empty() || order.len() > channels.len() {
+        return None;
+    }
+
+    let mut sources = [None; 4];
+    let mut fills = Vec::new();
+
+    for (i, &chr) in order.iter().enumerate() {
+        match (chr, channels.find(chr)) {
+            ('x', _) => {}
+            ('0', _) => fills.push(Source::Zero),
+            ('1', _) => fills.push(Source::One),
+            (_, Some(channel)) => match sources[channel] {
+                Some(source) => fills.push(source),
+                None => sources[channel] = Some(Source::Component(i)),
+            },
+            (_, None) => return None,
+        }
+    }
+
+    let mut unset = sources[..channels.len()]
+        .iter_mut()
+        .filter(|source| source.is_none());
+    for fill in fills {
+        *unset.next()? = Some(fill);
+    }
+
+    let [r, g, b, a] = sources;
+
+    Some([
+        r.unwrap_or(Source::Zero),
+        g.unwrap_or(Source::Zero),
+        b.unwrap_or(Source::Zero),
+        a.unwrap_or(Source::One),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `order` lowercased, as [`PixelFormat::valid_order`] does.
+    fn sources(order: &str) -> Result<[Source; 4], String> {
+        rgba_order(&order.to_ascii_lowercase().chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn accepted_orders() {
+        use Source::{Component, One, Zero};
+
+        assert_eq!(
+            sources("xRGB"),
+            Ok([Component(1), Component(2), Component(3), One])
+        );
+        assert_eq!(
+            sources("BGR0"),
+            Ok([Component(2), Component(1), Component(0), Zero])
+        );
+        assert_eq!(
+            sources("RRR1"),
+            Ok([Component(0), Component(0), Component(0), One])
+        );
+        assert_eq!(sources("GA"), Ok([Zero, Component(0), Zero, Component(1)]));
+    }
+
+    #[test]
+    fn rejected_orders() {
+        assert!(sources("rgbaa").is_err());
+        assert!(sources("q").is_err());
+    }
 }
@@ -0,0 +1,268 @@
+use iced::{
+    alignment::Vertical,
+    widget::{Checkbox, Column, Row, checkbox, column, combo_box, row, text},
+};
+
+use super::message::{Message, TextInput};
+use crate::{LABEL_WIDTH, SPACING};
+
+#[derive(Debug)]
+pub struct SwizzleState {
+    pub state: combo_box::State<Swizzle>,
+    pub selected: Swizzle,
+    pub block_height: String,
+    pub swap_words: bool,
+    pub gx2_state: combo_box::State<Gx2TileMode>,
+    pub gx2_tile_mode: Gx2TileMode,
+    pub gx2_swizzle: String,
+}
+
+impl Default for SwizzleState {
+    fn default() -> Self {
+        Self {
+            state: combo_box::State::new(Swizzle::all()),
+            selected: Default::default(),
+            block_height: String::new(),
+            swap_words: true,
+            gx2_state: combo_box::State::new(Gx2TileMode::all()),
+            gx2_tile_mode: Default::default(),
+            gx2_swizzle: 0.to_string(),
+        }
+    }
+}
+
+impl SwizzleState {
+    /// `height` is the image height in pixels, or in blocks for compressed
+    /// formats, if known.
+    pub fn view(&self, height: Option<usize>) -> Column<'_, Message> {
+        let label = text("Swizzle:").width(LABEL_WIDTH);
+        let swizzle = combo_box(
+            &self.state,
+            "",
+            Some(&self.selected),
+            Message::SwizzleChanged,
+        )
+        .width(120);
+
+        let row = row![label, swizzle]
+            .spacing(SPACING)
+            .align_y(Vertical::Center);
+
+        let block_height: Option<Row<Message>> = if self.selected == Swizzle::BlockLinear {
+            let input = TextInput::GobBlockHeight.view("Block height:", &self.block_height);
+            let auto = height.map(|height| text(format!("auto: {}", suggest_block_height(height))));
+
+            input.push_maybe(auto).into()
+        } else {
+            None
+        };
+
+        let swap_words: Option<Checkbox<Message>> = if self.selected == Swizzle::Xenos {
+            checkbox("Swap 16-bit words", self.swap_words)
+                .on_toggle(Message::SwapWordsChanged)
+                .into()
+        } else {
+            None
+        };
+
+        let gx2: Option<Column<Message>> = if self.selected == Swizzle::Gx2 {
+            let label = text("Tile mode:").width(LABEL_WIDTH);
+            let tile_mode = combo_box(
+                &self.gx2_state,
+                "",
+                Some(&self.gx2_tile_mode),
+                Message::Gx2TileModeChanged,
+            )
+            .width(120);
+            let tile_mode = row![label, tile_mode]
+                .spacing(SPACING)
+                .align_y(Vertical::Center);
+            let surface_swizzle = TextInput::Gx2Swizzle.view("Swizzle:", &self.gx2_swizzle);
+
+            column![tile_mode, surface_swizzle].spacing(SPACING).into()
+        } else {
+            None
+        };
+
+        column![row]
+            .push_maybe(block_height)
+            .push_maybe(swap_words)
+            .push_maybe(gx2)
+            .spacing(SPACING)
+    }
+
+    /// Whether the bytes of each 16-bit word are swapped before unswizzling.
+    pub fn swaps_words(&self) -> bool {
+        self.selected == Swizzle::Xenos && self.swap_words
+    }
+
+    /// Swizzle value of a GX2 surface, holding the pipe and bank swizzles.
+    pub fn gx2_swizzle(&self) -> Result<usize, String> {
+        self.gx2_swizzle
+            .parse()
+            .map_err(|_| "swizzle is empty".to_owned())
+    }
+
+    /// Block height in GOBs for block-linear layout. An empty field picks the
+    /// suggested one for an image `h` rows high.
+    pub fn block_height(&self, h: usize) -> Result<usize, String> {
+        if self.block_height.is_empty() {
+            return Ok(suggest_block_height(h));
+        }
+
+        match self.block_height.parse() {
+            Ok(block_height @ (1 | 2 | 4 | 8 | 16 | 32)) => Ok(block_height),
+            _ => Err("block height must be 1, 2, 4, 8, 16 or 32 GOBs".into()),
+        }
+    }
+}
+
+/// Block height the Tegra driver picks for the first mip level of an image `h`
+/// rows high: enough GOBs to cover it, rounded up to a power of two and at most
+/// 16, as in nouveau's `nvc0_tex_choose_tile_dims` and Switch-Toolbox's
+/// `GetBlockHeight`.
+fn suggest_block_height(h: usize) -> usize {
+    let mut block_height = h.div_ceil(8).next_power_of_two().clamp(1, 16);
+
+    // Blocks taller than needed waste memory, so halve them
+    while block_height > 1 && block_height / 2 * 8 >= h {
+        block_height /= 2;
+    }
+
+    block_height
+}
+
+/// Order in which a console stores the pixels of a texture, undone before the
+/// pixels are decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Swizzle {
+    #[default]
+    None,
+    /// GS local memory order of PSMCT32, PSMT8 or PSMT4, chosen by the bits per
+    /// pixel. This is how pixels sit in a dump of GS memory.
+    Ps2,
+    /// PSMT8 or PSMT4 pixels uploaded to GS memory as a PSMCT32 image half as
+    /// wide, stored in the row-major order of that image. Most game files use
+    /// this layout.
+    Ps2Ct32,
+    /// Blocks of 16 bytes by 8 rows.
+    Psp,
+    /// Morton order, with sides rounded up to a power of two.
+    Vita,
+    /// Nintendo Switch block-linear layout of 64x8 byte GOBs.
+    BlockLinear,
+    /// Xbox 360 tiled layout of 32x32 pixel macro tiles.
+    Xenos,
+    /// Wii U surface layout of the selected tile mode.
+    Gx2,
+}
+
+impl Swizzle {
+    fn all() -> Vec<Self> {
+        vec![
+            Self::None,
+            Self::Ps2,
+            Self::Ps2Ct32,
+            Self::Psp,
+            Self::Vita,
+            Self::BlockLinear,
+            Self::Xenos,
+            Self::Gx2,
+        ]
+    }
+}
+
+impl std::fmt::Display for Swizzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Ps2 => write!(f, "PS2 GS memory"),
+            Self::Ps2Ct32 => write!(f, "PS2 CT32"),
+            Self::Psp => write!(f, "PSP"),
+            Self::Vita => write!(f, "PS Vita"),
+            Self::BlockLinear => write!(f, "Switch"),
+            Self::Xenos => write!(f, "Xbox 360"),
+            Self::Gx2 => write!(f, "Wii U GX2"),
+        }
+    }
+}
+
+/// 2D tile modes of GX2 surfaces. Thick modes are only used for 3D textures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Gx2TileMode {
+    LinearAligned,
+    Tiled1DThin1,
+    #[default]
+    Tiled2DThin1,
+    Tiled2DThin2,
+    Tiled2DThin4,
+    Tiled2BThin1,
+    Tiled2BThin2,
+    Tiled2BThin4,
+}
+
+impl Gx2TileMode {
+    fn all() -> Vec<Self> {
+        vec![
+            Self::LinearAligned,
+            Self::Tiled1DThin1,
+            Self::Tiled2DThin1,
+            Self::Tiled2DThin2,
+            Self::Tiled2DThin4,
+            Self::Tiled2BThin1,
+            Self::Tiled2BThin2,
+            Self::Tiled2BThin4,
+        ]
+    }
+
+    /// Ratio of the height to the width of macro tiles, relative to square ones.
+    pub fn aspect_ratio(&self) -> usize {
+        match self {
+            Self::Tiled2DThin2 | Self::Tiled2BThin2 => 2,
+            Self::Tiled2DThin4 | Self::Tiled2BThin4 => 4,
+            _ => 1,
+        }
+    }
+
+    pub fn is_bank_swapped(&self) -> bool {
+        matches!(
+            self,
+            Self::Tiled2BThin1 | Self::Tiled2BThin2 | Self::Tiled2BThin4
+        )
+    }
+}
+
+impl std::fmt::Display for Gx2TileMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LinearAligned => write!(f, "Linear aligned"),
+            Self::Tiled1DThin1 => write!(f, "1D thin1"),
+            Self::Tiled2DThin1 => write!(f, "2D thin1"),
+            Self::Tiled2DThin2 => write!(f, "2D thin2"),
+            Self::Tiled2DThin4 => write!(f, "2D thin4"),
+            Self::Tiled2BThin1 => write!(f, "2B thin1"),
+            Self::Tiled2BThin2 => write!(f, "2B thin2"),
+            Self::Tiled2BThin4 => write!(f, "2B thin4"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_heights() {
+        for (h, block_height) in [
+            (8, 1),
+            (16, 2),
+            (64, 8),
+            (72, 16),
+            (128, 16),
+            (256, 16),
+            (720, 16),
+        ] {
+            assert_eq!(suggest_block_height(h), block_height, "height {h}");
+        }
+    }
+}